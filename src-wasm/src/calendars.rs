@@ -0,0 +1,388 @@
+//! Calendar lookup for a CPM calculation
+//!
+//! Every task works on the project calendar unless its `calendarId` names one
+//! of the engine's calendars (a crew working 6-day weeks, curing that runs
+//! 7 days a week). Lags are counted on the calendar picked by the
+//! `LagCalendar` rule.
+//!
+//! A `WorkCalendar` pairs a calendar with the schedule's time unit, so the
+//! CPM passes read the same whether they count whole days (inclusive
+//! "YYYY-MM-DD" dates: a 1-day task starts and finishes on the same day) or
+//! hours ("YYYY-MM-DDTHH:MM" moments: a task finishes when its last hour ends).
+//! The passes carry dates as `Moment` ordinals; task dates are only parsed
+//! when a calculation starts and formatted when it writes its results.
+//!
+//! A date search that finds no working time is recorded on the `Calendars`
+//! (see `take_error`) and the date is left where it was, so one bad
+//! calendar cannot hang a calculation.
+//!
+//! When scheduling in days, dates covered by the calendar's `DayIndex` are
+//! looked up instead of walked (see `day_index.rs`).
+
+use crate::day_index::{DayIndex, DayIndexes};
+use crate::date_utils::{
+    add_work_days, add_work_minutes, calc_work_days, calc_work_days_difference, format_moment, parse_moment,
+    work_day_on_or_before, work_minutes_between, work_time_on_or_before, DateError,
+};
+use crate::types::{Calendar, LagCalendar, ScheduleOptions, Task, TimeUnit, WorkPeriod};
+use chrono::{Datelike, NaiveDate};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A point on the schedule's time line: a day number (days from the Common
+/// Era, as in `DayIndex`) when scheduling in days, a minute number (minutes
+/// from the start of the Common Era) when scheduling in hours
+pub type Moment = i32;
+
+/// Minutes in a day
+const DAY_MINUTES: i32 = 24 * 60;
+
+/// Calendars available to one calculation
+pub struct Calendars<'a> {
+    /// Project calendar - used by tasks without a (known) calendar ID
+    project: &'a Calendar,
+    /// Named calendars by ID
+    named: &'a HashMap<String, Calendar>,
+    /// Working-day tables of the project and named calendars
+    indexes: Option<&'a DayIndexes>,
+    /// Which calendar lags are counted on
+    lag_rule: LagCalendar,
+    unit: TimeUnit,
+    /// Every day working around the clock - the "24-hour" lag calendar
+    continuous: Calendar,
+    /// First date search that found no working time
+    error: RefCell<Option<DateError>>,
+}
+
+impl<'a> Calendars<'a> {
+    pub fn new(project: &'a Calendar, named: &'a HashMap<String, Calendar>, indexes: Option<&'a DayIndexes>, options: &ScheduleOptions) -> Self {
+        let all_day = vec![WorkPeriod { start: "00:00".to_string(), end: "24:00".to_string() }];
+        Calendars {
+            project,
+            named,
+            indexes,
+            lag_rule: options.lag_calendar,
+            unit: options.time_unit,
+            continuous: Calendar {
+                working_days: (0..7).collect(),
+                work_hours: (0..7).map(|day: i32| (day.to_string(), all_day.clone())).collect(),
+                ..Calendar::default()
+            },
+            error: RefCell::new(None),
+        }
+    }
+
+    fn with_unit<'c>(&'c self, calendar: &'c Calendar, index: Option<&'c DayIndex>) -> WorkCalendar<'c> {
+        // Tables count whole days only
+        let index = index.filter(|_| self.unit == TimeUnit::Days);
+        WorkCalendar { calendar, index, unit: self.unit, error: &self.error }
+    }
+
+    /// Take the first date error met since the last call, if any
+    pub fn take_error(&self) -> Option<DateError> {
+        self.error.borrow_mut().take()
+    }
+
+    /// The project calendar
+    pub fn project(&self) -> WorkCalendar<'_> {
+        self.with_unit(self.project, self.indexes.map(DayIndexes::project))
+    }
+
+    /// Calendar a task's own dates and duration are counted on
+    ///
+    /// Unknown calendar IDs fall back to the project calendar.
+    pub fn task(&self, task: &Task) -> WorkCalendar<'_> {
+        match task.calendar_id.as_ref().and_then(|id| Some((id, self.named.get(id)?))) {
+            Some((id, calendar)) => self.with_unit(calendar, self.indexes.and_then(|i| i.named(id))),
+            None => self.project(),
+        }
+    }
+
+    /// Calendar the lag of a link from `pred` to `succ` is counted on
+    pub fn lag(&self, pred: &Task, succ: &Task) -> WorkCalendar<'_> {
+        self.lag_between(self.task(pred), self.task(succ))
+    }
+
+    /// Lag calendar given the predecessor's and successor's calendars
+    pub fn lag_between<'c>(&'c self, pred: WorkCalendar<'c>, succ: WorkCalendar<'c>) -> WorkCalendar<'c> {
+        match self.lag_rule {
+            LagCalendar::Predecessor => pred,
+            LagCalendar::Successor => succ,
+            LagCalendar::TwentyFourHour => self.with_unit(&self.continuous, None),
+        }
+    }
+}
+
+/// A calendar counted in the schedule's time unit
+#[derive(Clone, Copy)]
+pub struct WorkCalendar<'a> {
+    calendar: &'a Calendar,
+    /// Working-day table of `calendar` (days only)
+    index: Option<&'a DayIndex>,
+    unit: TimeUnit,
+    error: &'a RefCell<Option<DateError>>,
+}
+
+impl WorkCalendar<'_> {
+    /// Read a task date ("YYYY-MM-DD", or "YYYY-MM-DDTHH:MM" in hours)
+    ///
+    /// In days any time of day is dropped; in hours a plain date is midnight.
+    pub fn parse(&self, date: &str) -> Option<Moment> {
+        match self.unit {
+            TimeUnit::Days => NaiveDate::parse_from_str(day_of(date), "%Y-%m-%d").ok().map(|d| d.num_days_from_ce()),
+            TimeUnit::Hours => {
+                let (day, minute) = parse_moment(date)?;
+                day.num_days_from_ce().checked_mul(DAY_MINUTES)?.checked_add(minute as i32)
+            }
+        }
+    }
+
+    /// Write a moment as a task date
+    pub fn format(&self, moment: Moment) -> String {
+        let date = |day: i32| NaiveDate::from_num_days_from_ce_opt(day);
+        match self.unit {
+            TimeUnit::Days => date(moment).map(|d| d.format("%Y-%m-%d").to_string()),
+            TimeUnit::Hours => date(moment.div_euclid(DAY_MINUTES))
+                .map(|d| format_moment(d, moment.rem_euclid(DAY_MINUTES) as u32)),
+        }
+        .unwrap_or_default()
+    }
+
+    /// Result of a date search, or `fallback` after recording its error
+    fn checked(&self, result: Result<Moment, DateError>, fallback: Moment) -> Moment {
+        result.unwrap_or_else(|e| {
+            self.error.borrow_mut().get_or_insert(e);
+            fallback
+        })
+    }
+
+    /// Run a `date_utils` search on the written form of a moment
+    fn walk(&self, moment: Moment, search: impl FnOnce(&str) -> Result<String, DateError>) -> Result<Moment, DateError> {
+        search(&self.format(moment)).map(|date| self.parse(&date).unwrap_or(moment))
+    }
+
+    /// `add_work_days` on this calendar, from the table when possible
+    fn add_days(&self, day: Moment, days: i32) -> Result<Moment, DateError> {
+        match self.index.and_then(|index| index.add_work_days(day, days)) {
+            Some(day) => Ok(day),
+            None => self.walk(day, |date| add_work_days(date, days, self.calendar)),
+        }
+    }
+
+    /// `work_day_on_or_before` on this calendar, from the table when possible
+    fn day_on_or_before(&self, day: Moment) -> Result<Moment, DateError> {
+        match self.index.and_then(|index| index.work_day_on_or_before(day)) {
+            Some(day) => Ok(day),
+            None => self.walk(day, |date| work_day_on_or_before(date, self.calendar)),
+        }
+    }
+
+    fn add_minutes(&self, moment: Moment, minutes: i64) -> Result<Moment, DateError> {
+        self.walk(moment, |date| add_work_minutes(date, minutes, self.calendar))
+    }
+
+    fn time_on_or_before(&self, moment: Moment) -> Result<Moment, DateError> {
+        self.walk(moment, |date| work_time_on_or_before(date, self.calendar))
+    }
+
+    /// First moment on or after `date` that work can start
+    pub fn start_on_or_after(&self, date: Moment) -> Moment {
+        let result = match self.unit {
+            TimeUnit::Days => self.add_days(date, 0),
+            TimeUnit::Hours => self.add_minutes(date, 0),
+        };
+        self.checked(result, date)
+    }
+
+    /// Latest moment on or before `date` that work can finish
+    pub fn finish_on_or_before(&self, date: Moment) -> Moment {
+        let result = match self.unit {
+            TimeUnit::Days => self.day_on_or_before(date),
+            TimeUnit::Hours => self.time_on_or_before(date),
+        };
+        self.checked(result, date)
+    }
+
+    /// Finish of `duration` units of work starting at `start`
+    pub fn finish(&self, start: Moment, duration: i32) -> Moment {
+        let result = match self.unit {
+            TimeUnit::Days => self.add_days(start, get_duration_offset(duration)),
+            TimeUnit::Hours => self.add_minutes(start, minutes(duration.max(0))),
+        };
+        self.checked(result, start)
+    }
+
+    /// Start of `duration` units of work finishing at `finish`
+    pub fn start_for_finish(&self, finish: Moment, duration: i32) -> Moment {
+        let result = match self.unit {
+            TimeUnit::Days => self.add_days(finish, -get_duration_offset(duration)),
+            TimeUnit::Hours if duration > 0 => self.add_minutes(finish, -minutes(duration)),
+            // A milestone starts where it finishes
+            TimeUnit::Hours => self.time_on_or_before(finish),
+        };
+        self.checked(result, finish)
+    }
+
+    /// Earliest start of work following work that finishes at `finish`
+    pub fn start_after(&self, finish: Moment) -> Moment {
+        let result = match self.unit {
+            TimeUnit::Days => self.add_days(finish, 1),
+            TimeUnit::Hours => self.add_minutes(finish, 0),
+        };
+        self.checked(result, finish)
+    }
+
+    /// Latest finish of work preceding work that starts at `start`
+    pub fn finish_before(&self, start: Moment) -> Moment {
+        let result = match self.unit {
+            TimeUnit::Days => self.add_days(start, -1),
+            TimeUnit::Hours => self.time_on_or_before(start),
+        };
+        self.checked(result, start)
+    }
+
+    /// `date` moved by `units` of work (a lag) - unchanged for zero
+    pub fn shift(&self, date: Moment, units: i32) -> Moment {
+        let result = match self.unit {
+            _ if units == 0 => return date,
+            TimeUnit::Days => self.add_days(date, units),
+            TimeUnit::Hours => self.add_minutes(date, minutes(units)),
+        };
+        self.checked(result, date)
+    }
+
+    /// Units of work from `from` to `to` (negative if `to` is earlier)
+    pub fn between(&self, from: Moment, to: Moment) -> i32 {
+        match self.unit {
+            TimeUnit::Days => self.index.and_then(|index| index.work_days_difference(from, to))
+                .unwrap_or_else(|| calc_work_days_difference(&self.format(from), &self.format(to), self.calendar)),
+            TimeUnit::Hours => (work_minutes_between(&self.format(from), &self.format(to), self.calendar) / 60) as i32,
+        }
+    }
+
+    /// Duration of a bar from `start` to `end`
+    pub fn span(&self, start: Moment, end: Moment) -> i32 {
+        match self.unit {
+            TimeUnit::Days => self.index.and_then(|index| index.work_days_inclusive(start, end))
+                .unwrap_or_else(|| calc_work_days(&self.format(start), &self.format(end), self.calendar)),
+            TimeUnit::Hours => self.between(start, end).max(0),
+        }
+    }
+
+    /// A start date entered by the user (constraint, actual start), None if
+    /// it isn't a date
+    ///
+    /// In hours a plain date means the day's first working moment; in days
+    /// any time of day is dropped.
+    pub fn as_start(&self, date: &str) -> Option<Moment> {
+        let moment = self.parse(date)?;
+        match self.unit {
+            TimeUnit::Hours if !date.contains('T') => Some(self.start_on_or_after(moment)),
+            _ => Some(moment),
+        }
+    }
+
+    /// A finish date entered by the user (constraint, deadline, actual
+    /// finish), None if it isn't a date
+    ///
+    /// In hours a plain date means the end of that day's working time; in
+    /// days any time of day is dropped.
+    pub fn as_finish(&self, date: &str) -> Option<Moment> {
+        let moment = self.parse(date)?;
+        match self.unit {
+            TimeUnit::Hours if !date.contains('T') => {
+                let end_of_day = moment.saturating_add(DAY_MINUTES);
+                Some(self.checked(self.time_on_or_before(end_of_day), end_of_day))
+            }
+            _ => Some(moment),
+        }
+    }
+}
+
+/// Get duration offset for CPM date calculations
+/// EF = ES + Duration - 1
+/// Returns 0 for milestones (duration=0)
+fn get_duration_offset(duration: i32) -> i32 {
+    if duration <= 0 {
+        0
+    } else {
+        duration - 1
+    }
+}
+
+/// Hours of work in minutes
+fn minutes(hours: i32) -> i64 {
+    hours as i64 * 60
+}
+
+/// Date part of a date or moment
+fn day_of(date: &str) -> &str {
+    date.split_once('T').map_or(date, |(day, _)| day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::task;
+
+    fn working_days(days: &[i32]) -> Calendar {
+        Calendar { working_days: days.to_vec(), ..Calendar::default() }
+    }
+
+    /// A Monday to Saturday calendar named "six", and one without working days named "none"
+    fn named() -> HashMap<String, Calendar> {
+        HashMap::from([
+            ("six".to_string(), working_days(&[1, 2, 3, 4, 5, 6])),
+            ("none".to_string(), working_days(&[])),
+        ])
+    }
+
+    fn on(calendar_id: Option<&str>) -> Task {
+        Task { calendar_id: calendar_id.map(str::to_string), ..task("A", 1) }
+    }
+
+    #[test]
+    fn a_task_works_on_its_named_calendar_or_else_the_project_one() {
+        let (project, named) = (working_days(&[1, 2, 3, 4, 5]), named());
+        let calendars = Calendars::new(&project, &named, None, &ScheduleOptions::default());
+        // Two days from Friday 2024-01-12
+        let finish = |calendar_id| {
+            let calendar = calendars.task(&on(calendar_id));
+            let friday = calendar.parse("2024-01-12").unwrap();
+            calendar.format(calendar.finish(friday, 2))
+        };
+
+        assert_eq!(finish(Some("six")), "2024-01-13");
+        assert_eq!(finish(None), "2024-01-15");
+        assert_eq!(finish(Some("unknown")), "2024-01-15");
+    }
+
+    #[test]
+    fn lags_count_on_the_calendar_the_rule_picks() {
+        let (project, named) = (working_days(&[1, 2, 3, 4, 5]), named());
+        let (pred, succ) = (on(None), on(Some("six")));
+        // A lag of 2 from Friday 2024-01-12
+        let shifted = |lag_calendar| {
+            let options = ScheduleOptions { lag_calendar, ..ScheduleOptions::default() };
+            let calendars = Calendars::new(&project, &named, None, &options);
+            let calendar = calendars.lag(&pred, &succ);
+            calendar.format(calendar.shift(calendar.parse("2024-01-12").unwrap(), 2))
+        };
+
+        assert_eq!(shifted(LagCalendar::Predecessor), "2024-01-16");
+        assert_eq!(shifted(LagCalendar::Successor), "2024-01-15");
+        assert_eq!(shifted(LagCalendar::TwentyFourHour), "2024-01-14");
+    }
+
+    #[test]
+    fn a_calendar_without_working_time_records_an_error_and_leaves_the_date() {
+        let (project, named) = (working_days(&[1, 2, 3, 4, 5]), named());
+        let calendars = Calendars::new(&project, &named, None, &ScheduleOptions::default());
+        let calendar = calendars.task(&on(Some("none")));
+        let monday = calendar.parse("2024-01-08").unwrap();
+
+        assert_eq!(calendar.finish(monday, 3), monday);
+        assert!(calendars.take_error().is_some());
+        assert!(calendars.take_error().is_none());
+    }
+}
@@ -1,44 +1,30 @@
 //! CPM (Critical Path Method) calculation engine
 //! 
-//! Implements forward pass, backward pass, float calculation, and critical path marking
+//! Implements forward pass, backward pass, float calculation, critical path marking
+//! and schedule health (see `health.rs`).
+//! Both passes are driven by a topological order of the schedule network
+//! (see `graph.rs`), so each task is visited exactly once.
 
-use crate::types::{Task, Calendar, CPMResult, CPMStats};
-use crate::date_utils::{add_work_days, calc_work_days, calc_work_days_difference, today};
+use crate::types::{Task, CPMResult, CPMStats, ConstraintType, CriticalPathMode, Dependency, DependencyCycle, DrivingPathStep, LinkAnalysis, LinkType, ProgressMode, RowType, ScheduleOptions, SchedulingMode, StartDriver};
+use crate::calendars::{Calendars, Moment, WorkCalendar};
+use crate::graph::{TaskGraph, Visit};
+use crate::health::assess_health;
+use crate::task_index::TaskIndex;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use js_sys::Date;
-
-const MAX_CPM_ITERATIONS: usize = 50;
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use crate::utils::now_ms;
 
 /// Successor map entry
 #[derive(Clone)]
 pub(crate) struct SuccessorEntry {
     id: String,
-    link_type: String,
+    link_type: LinkType,
     lag: i32,
 }
 
-/// Get duration offset for CPM date calculations
-/// EF = ES + Duration - 1
-/// Returns 0 for milestones (duration=0)
-fn get_duration_offset(duration: i32) -> i32 {
-    if duration <= 0 {
-        0
-    } else {
-        duration - 1
-    }
-}
-
-/// Get depth level of a task in hierarchy
-fn get_depth(task_id: &str, tasks: &[Task], depth: i32) -> i32 {
-    if let Some(task) = tasks.iter().find(|t| t.id == task_id) {
-        if let Some(parent_id) = &task.parent_id {
-            return get_depth(parent_id, tasks, depth + 1);
-        }
-    }
-    depth
-}
-
 /// Build a map of task successors for efficient backward pass
 fn build_successor_map(tasks: &[Task], blank_row_ids: &HashSet<String>) -> HashMap<String, Vec<SuccessorEntry>> {
     let mut successor_map: HashMap<String, Vec<SuccessorEntry>> = HashMap::new();
@@ -67,7 +53,7 @@ fn build_successor_map(tasks: &[Task], blank_row_ids: &HashSet<String>) -> HashM
             if let Some(successors) = successor_map.get_mut(&dep.id) {
                 successors.push(SuccessorEntry {
                     id: task.id.clone(),
-                    link_type: dep.link_type.clone(),
+                    link_type: dep.link_type,
                     lag: dep.lag,
                 });
             }
@@ -77,657 +63,1746 @@ fn build_successor_map(tasks: &[Task], blank_row_ids: &HashSet<String>) -> HashM
     successor_map
 }
 
-/// Forward pass - calculate Early Start (ES) and Early Finish (EF)
-pub fn forward_pass(tasks: &mut [Task], calendar: &Calendar, parent_ids: &HashSet<String>, blank_row_ids: &HashSet<String>) {
-    let mut iterations = 0;
-    let mut changed = true;
+/// Lookup sets, successor map and topological order of a task list
+///
+/// Kept by the engine between calculations and only rebuilt when the
+/// hierarchy or the logic changes.
+pub struct Network {
+    pub parent_ids: HashSet<String>,
+    pub blank_row_ids: HashSet<String>,
+    successor_map: HashMap<String, Vec<SuccessorEntry>>,
+    pub graph: TaskGraph,
+    /// Parent tasks, deepest first, so rollups see child summaries first
+    rollup_order: Vec<usize>,
+    /// Project finish the last calculation derived late dates from
+    project_end: Option<Moment>,
+    /// Data date the last calculation scheduled from
+    data_start: Option<Moment>,
+}
+
+impl Network {
+    /// Rebuild after the hierarchy or logic changed, keeping the project
+    /// finish and data date of the last calculation
+    pub fn rebuild(&mut self, tasks: &[Task], index: &TaskIndex) {
+        let project_end = std::mem::take(&mut self.project_end);
+        let data_start = std::mem::take(&mut self.data_start);
+        *self = Network::build(tasks, index);
+        self.project_end = project_end;
+        self.data_start = data_start;
+    }
     
-    while changed && iterations < MAX_CPM_ITERATIONS {
-        changed = false;
-        iterations += 1;
+    pub fn build(tasks: &[Task], index: &TaskIndex) -> Network {
+        // O(N) pre-computation - build lookup sets ONCE
+        let parent_ids: HashSet<String> = tasks.iter()
+            .filter_map(|t| t.parent_id.as_ref())
+            .cloned()
+            .collect();
         
-        // Build a map of task IDs to their current dates for dependency lookup
-        let task_dates: HashMap<String, (String, String)> = tasks.iter()
-            .map(|t| (t.id.clone(), (t.start.clone(), t.end.clone())))
+        // Build set of blank row IDs to skip in CPM
+        let blank_row_ids: HashSet<String> = tasks.iter()
+            .filter(|t| t.row_type == Some(RowType::Blank))
+            .map(|t| t.id.clone())
             .collect();
         
-        for i in 0..tasks.len() {
-            let task_id = tasks[i].id.clone();
-            
-            // Skip blank rows - they have no dates
-            if blank_row_ids.contains(&task_id) {
-                continue;
-            }
-            
-            // Skip parent tasks - their dates are calculated from children
-            if parent_ids.contains(&task_id) {
+        let successor_map = build_successor_map(tasks, &blank_row_ids);
+        let graph = TaskGraph::build(tasks, &parent_ids, &blank_row_ids);
+        
+        // Depths only order the rollups; ties keep task order
+        let depths = index.depths(tasks);
+        let mut rollup_order: Vec<usize> = (0..tasks.len())
+            .filter(|&i| parent_ids.contains(&tasks[i].id))
+            .collect();
+        rollup_order.sort_by_key(|&i| std::cmp::Reverse(depths[i]));
+        
+        Network {
+            parent_ids,
+            blank_row_ids,
+            successor_map,
+            graph,
+            rollup_order,
+            project_end: None,
+            data_start: None,
+        }
+    }
+}
+
+/// Change tracking for incremental recalculation, indexed by graph node
+///
+/// A pass revisits a node when it is dirty or when a node it reads from
+/// changed earlier in the same pass. A revisited node passes the change on
+/// only if its own dates moved, so an edit stops propagating as soon as it
+/// is absorbed by float.
+pub struct Changes {
+    dirty: Vec<bool>,
+    /// Early dates moved (forward pass, ALAP placement)
+    early: Vec<bool>,
+    /// Late dates moved (backward pass)
+    late: Vec<bool>,
+    /// Every late date must be revisited (the project finish moved)
+    all_late: bool,
+}
+
+impl Changes {
+    /// Everything dirty: a full calculation
+    pub fn all(graph: &TaskGraph) -> Changes {
+        let nodes = graph.node_count();
+        Changes {
+            dirty: vec![true; nodes],
+            early: vec![false; nodes],
+            late: vec![false; nodes],
+            all_late: true,
+        }
+    }
+    
+    /// Only the given tasks are dirty
+    pub fn from_dirty(graph: &TaskGraph, dirty_tasks: &[usize]) -> Changes {
+        let nodes = graph.node_count();
+        let mut changes = Changes {
+            dirty: vec![false; nodes],
+            early: vec![false; nodes],
+            late: vec![false; nodes],
+            all_late: false,
+        };
+        for &i in dirty_tasks {
+            changes.mark_dirty(graph, i);
+        }
+        changes
+    }
+    
+    /// Mark a task dirty (both nodes of a summary)
+    fn mark_dirty(&mut self, graph: &TaskGraph, i: usize) {
+        self.dirty[i] = true;
+        self.dirty[graph.node(Visit::SummaryStart(i))] = true;
+    }
+    
+    /// Whether the forward pass must recalculate a node
+    fn revisit_early(&self, graph: &TaskGraph, node: usize) -> bool {
+        self.dirty[node] || graph.inputs(node).iter().any(|&u| self.early[u])
+    }
+    
+    /// Whether the backward pass must recalculate a node
+    fn revisit_late(&self, graph: &TaskGraph, node: usize) -> bool {
+        self.all_late || self.dirty[node] || self.early[node]
+            || graph.outputs(node).any(|u| self.late[u])
+    }
+    
+    /// Whether a task's own early dates or those of its inputs changed
+    /// (for a summary, the inputs of its start node as well)
+    fn early_touched(&self, graph: &TaskGraph, i: usize) -> bool {
+        let start = graph.node(Visit::SummaryStart(i));
+        self.early[i] || self.revisit_early(graph, i) || self.early[start] || self.revisit_early(graph, start)
+    }
+    
+    /// Whether a task's float can have changed: its own dates, or the early
+    /// dates of anything it drives
+    fn float_touched(&self, graph: &TaskGraph, i: usize) -> bool {
+        self.all_late || self.dirty[i] || self.early[i] || self.late[i]
+            || graph.outputs(i).any(|u| self.early[u])
+    }
+}
+
+/// A task's scheduled dates while a calculation runs (None = unscheduled)
+///
+/// Read from the task's date strings when a calculation starts and written
+/// back when it is done, so the passes never parse or format a date.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Dates {
+    pub start: Option<Moment>,
+    pub end: Option<Moment>,
+    pub late_start: Option<Moment>,
+    pub late_finish: Option<Moment>,
+}
+
+impl Dates {
+    fn read(task: &Task, calendar: WorkCalendar) -> Dates {
+        let parse = |date: &Option<String>| date.as_deref().and_then(|d| calendar.parse(d));
+        Dates {
+            start: calendar.parse(&task.start),
+            end: calendar.parse(&task.end),
+            late_start: parse(&task.late_start),
+            late_finish: parse(&task.late_finish),
+        }
+    }
+    
+    /// Write the dates that differ from `read` back to the task, leaving
+    /// the rest as entered
+    fn write(&self, read: &Dates, task: &mut Task, calendar: WorkCalendar) {
+        let format = |date: Option<Moment>| date.map(|d| calendar.format(d));
+        if self.start != read.start {
+            task.start = format(self.start).unwrap_or_default();
+        }
+        if self.end != read.end {
+            task.end = format(self.end).unwrap_or_default();
+        }
+        if self.late_start != read.late_start {
+            task.late_start = format(self.late_start);
+        }
+        if self.late_finish != read.late_finish {
+            task.late_finish = format(self.late_finish);
+        }
+    }
+}
+
+/// Hash of everything the CPM passes write to a task, to find the tasks a
+/// calculation changed
+fn fingerprint(task: &Task) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    task.start.hash(&mut hasher);
+    task.end.hash(&mut hasher);
+    task.duration.hash(&mut hasher);
+    task.late_start.hash(&mut hasher);
+    task.late_finish.hash(&mut hasher);
+    task.total_float_days.hash(&mut hasher);
+    task.free_float_days.hash(&mut hasher);
+    task.is_critical.hash(&mut hasher);
+    task.is_near_critical.hash(&mut hasher);
+    task.deadline_variance.hash(&mut hasher);
+    task.driven_by.hash(&mut hasher);
+    task.in_cycle.hash(&mut hasher);
+    task.health.hash(&mut hasher);
+    for link in task.predecessor_links.iter().flatten() {
+        link.predecessor_id.hash(&mut hasher);
+        link.relative_float.hash(&mut hasher);
+        link.driving.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Forward pass - calculate Early Start (ES) and Early Finish (EF)
+///
+/// Tasks are visited once in topological order, so every predecessor (and
+/// every child of a summary) is final by the time it is read. Only nodes
+/// that `changes` marks for a revisit are recalculated.
+/// `data_date` is the first working day on or after the project's data date.
+///
+/// A summary is visited twice: its start step turns the summary's own
+/// predecessor logic into a start floor for all of its children, and its
+/// finish step rolls the children back up for the summary's successors.
+///
+/// Returns the start floor each summary imposed on its children.
+#[allow(clippy::too_many_arguments)]
+pub fn forward_pass(tasks: &mut [Task], dates: &mut [Dates], calendars: &Calendars, options: &ScheduleOptions, data_date: Option<Moment>, graph: &TaskGraph, parent_ids: &HashSet<String>, changes: &mut Changes) -> Vec<Option<Moment>> {
+    let mut summary_floor: Vec<Option<Moment>> = vec![None; tasks.len()];
+    // ALAP tasks, and summaries rolling them up, are stored at their ALAP
+    // position rather than their early dates - always recalculate them and
+    // pass them on as changed
+    let mut alap: Vec<bool> = vec![false; tasks.len()];
+    
+    for &visit in &graph.order {
+        let node = graph.node(visit);
+        let revisit = changes.revisit_early(graph, node);
+        let i = match visit {
+            Visit::SummaryStart(p) => {
+                // Always derived (cheap, not stored); counts as changed whenever revisited
+                summary_floor[p] = summary_start_floor(tasks, dates, p, calendars, graph, &summary_floor);
+                changes.early[node] = revisit;
                 continue;
             }
-            
-            // MANUAL MODE: Skip dependency-driven calculation
-            if tasks[i].scheduling_mode == "Manual" {
-                // If task has a start date and duration, ensure end is consistent
-                if !tasks[i].start.is_empty() && tasks[i].duration > 0 {
-                    let calculated_end = add_work_days(
-                        &tasks[i].start, 
-                        get_duration_offset(tasks[i].duration), 
-                        calendar
-                    );
-                    if tasks[i].end != calculated_end {
-                        tasks[i].end = calculated_end;
-                        changed = true;
-                    }
-                }
-                // Skip all dependency and constraint processing
-                continue;
+            Visit::Task(i) => i,
+        };
+        alap[i] = if graph.is_summary(i) {
+            graph.children[i].iter().any(|&c| alap[c])
+        } else {
+            tasks[i].constraint_type == ConstraintType::Alap
+        };
+        if !revisit && !alap[i] {
+            continue;
+        }
+        
+        let before = (dates[i].start, dates[i].end);
+        schedule_early(tasks, dates, i, calendars, options, data_date, graph, parent_ids, &summary_floor);
+        changes.early[node] = alap[i] || before != (dates[i].start, dates[i].end);
+    }
+    
+    summary_floor
+}
+
+/// Calculate one task's early dates in the forward pass
+#[allow(clippy::too_many_arguments)]
+fn schedule_early(tasks: &mut [Task], dates: &mut [Dates], i: usize, calendars: &Calendars, options: &ScheduleOptions, data_date: Option<Moment>, graph: &TaskGraph, parent_ids: &HashSet<String>, summary_floor: &[Option<Moment>]) {
+    // Parent tasks: roll up from children, which are already scheduled
+    if parent_ids.contains(&tasks[i].id) {
+        roll_up_parent_dates(tasks, dates, i, calendars, graph);
+        return;
+    }
+    let calendar = calendars.task(&tasks[i]);
+    
+    // MANUAL MODE: Skip dependency-driven calculation
+    if tasks[i].scheduling_mode == SchedulingMode::Manual {
+        // If task has a start date and duration, ensure end is consistent
+        if let Some(start) = dates[i].start.filter(|_| tasks[i].duration > 0) {
+            dates[i].end = Some(calendar.finish(start, tasks[i].duration));
+        }
+        // Skip all dependency and constraint processing
+        return;
+    }
+    
+    // PROGRESS: completed and in-progress tasks follow their actuals
+    match progress_state(&tasks[i]) {
+        ProgressState::Complete => {
+            schedule_complete(&tasks[i], &mut dates[i], calendar);
+            return;
+        }
+        ProgressState::InProgress => {
+            // Retained logic: remaining work still waits for predecessors
+            let remaining = remaining_duration(&tasks[i]);
+            let logic_start = match options.progress_mode {
+                ProgressMode::RetainedLogic => later(
+                    predecessor_start(tasks, dates, i, remaining, calendars, graph),
+                    inherited_floor(graph, summary_floor, i),
+                ),
+                ProgressMode::ProgressOverride => None,
+            };
+            schedule_in_progress(&tasks[i], &mut dates[i], remaining, logic_start, data_date, calendar);
+            return;
+        }
+        ProgressState::NotStarted => {}
+    }
+    
+    // AUTO MODE: Standard CPM dependency calculation.
+    // Nothing to start from (no data date, predecessor or constraint): unscheduled
+    let early = early_dates(tasks, dates, i, calendars, data_date, graph, summary_floor);
+    dates[i].start = early.map(|(start, _)| start);
+    dates[i].end = early.and_then(|(_, end)| end);
+}
+
+/// Early start and finish of a not-started, automatically scheduled task
+///
+/// After its predecessors and its summary's floor, moved by its constraint,
+/// and never before the data date. None if nothing determines a start.
+fn early_dates(tasks: &[Task], dates: &[Dates], i: usize, calendars: &Calendars, data_date: Option<Moment>, graph: &TaskGraph, summary_floor: &[Option<Moment>]) -> Option<(Moment, Option<Moment>)> {
+    let duration = tasks[i].duration;
+    let calendar = calendars.task(&tasks[i]);
+    let earliest_start = later(
+        predecessor_start(tasks, dates, i, duration, calendars, graph),
+        inherited_floor(graph, summary_floor, i),
+    );
+    
+    // Apply constraints to the early dates
+    let mut final_start = earliest_start;
+    let constraint = tasks[i].constraint_type;
+    let const_date = tasks[i].constraint_date.as_deref().filter(|d| !d.is_empty());
+    
+    if let Some(cd) = const_date {
+        match constraint {
+            ConstraintType::Snet => {
+                final_start = later(final_start, calendar.as_start(cd));
             }
-            
-            // AUTO MODE: Standard CPM dependency calculation
-            let mut earliest_start: Option<String> = None;
-            
-            // Process dependencies
-            for dep in &tasks[i].dependencies.clone() {
-                if let Some((pred_start, pred_end)) = task_dates.get(&dep.id) {
-                    if pred_start.is_empty() || pred_end.is_empty() {
-                        continue;
-                    }
-                    
-                    let link_type = &dep.link_type;
-                    let lag = dep.lag;
-                    
-                    let dep_start = match link_type.as_str() {
-                        "FS" => add_work_days(pred_end, 1 + lag, calendar),
-                        "SS" => add_work_days(pred_start, lag, calendar),
-                        "FF" => {
-                            let duration = tasks[i].duration;
-                            add_work_days(pred_end, -get_duration_offset(duration) + lag, calendar)
-                        }
-                        "SF" => {
-                            let duration = tasks[i].duration;
-                            add_work_days(pred_start, -get_duration_offset(duration) + lag, calendar)
-                        }
-                        _ => add_work_days(pred_end, 1 + lag, calendar),
-                    };
-                    
-                    if earliest_start.is_none() || dep_start > *earliest_start.as_ref().unwrap() {
-                        earliest_start = Some(dep_start);
-                    }
-                }
+            ConstraintType::Fnet => {
+                let implied_start = calendar.as_finish(cd).map(|finish| calendar.start_for_finish(finish, duration));
+                final_start = later(final_start, implied_start);
             }
-            
-            // Apply constraints
-            let mut final_start = earliest_start;
-            let constraint_type = tasks[i].constraint_type.to_lowercase();
-            let const_date = tasks[i].constraint_date.clone();
-            
-            match constraint_type.as_str() {
-                "snet" => {
-                    if let Some(cd) = const_date.clone() {
-                        if final_start.is_none() || cd > *final_start.as_ref().unwrap() {
-                            final_start = Some(cd);
-                        }
-                    }
-                }
-                "snlt" => {
-                    if let Some(cd) = const_date.clone() {
-                        let current = final_start.clone().unwrap_or_else(|| tasks[i].start.clone());
-                        if !current.is_empty() && cd < current {
-                            final_start = Some(cd);
-                        }
-                    }
-                }
-                "fnet" => {
-                    if let Some(cd) = const_date.clone() {
-                        let duration = tasks[i].duration;
-                        let implied_start = add_work_days(&cd, -get_duration_offset(duration), calendar);
-                        if final_start.is_none() || implied_start > *final_start.as_ref().unwrap() {
-                            final_start = Some(implied_start);
-                        }
-                    }
-                }
-                "fnlt" => {
-                    // FNLT does NOT affect forward pass - will be applied in backward pass
+            ConstraintType::Mso => {
+                // Mandatory: overrides logic and the data date
+                if let Some(start) = calendar.as_start(cd) {
+                    return Some((start, Some(calendar.finish(start, duration))));
                 }
-                "mfo" => {
-                    if let Some(cd) = const_date {
-                        let duration = tasks[i].duration;
-                        tasks[i].end = cd.clone();
-                        tasks[i].start = add_work_days(&cd, -get_duration_offset(duration), calendar);
-                        continue; // Skip normal calculation
-                    }
-                }
-                _ => {
-                    // ASAP or default
-                    if final_start.is_none() && tasks[i].start.is_empty() {
-                        final_start = Some(today());
-                    }
-                }
-            }
-            
-            if final_start.is_none() {
-                final_start = if tasks[i].start.is_empty() { None } else { Some(tasks[i].start.clone()) };
             }
-            
-            // Update if changed
-            if let Some(fs) = final_start {
-                if tasks[i].start != fs {
-                    tasks[i].start = fs.clone();
-                    changed = true;
-                }
-                
-                // Calculate end date (Early Finish)
-                let duration = tasks[i].duration;
-                if !tasks[i].start.is_empty() && duration >= 0 {
-                    let new_end = add_work_days(&tasks[i].start, get_duration_offset(duration), calendar);
-                    if tasks[i].end != new_end {
-                        tasks[i].end = new_end;
-                        changed = true;
-                    }
+            ConstraintType::Mfo => {
+                if let Some(end) = calendar.as_finish(cd) {
+                    return Some((calendar.start_for_finish(end, duration), Some(end)));
                 }
             }
+            // SNLT/FNLT only limit late dates (backward pass);
+            // ALAP is placed after the backward pass
+            ConstraintType::Snlt | ConstraintType::Fnlt | ConstraintType::Asap | ConstraintType::Alap => {}
         }
     }
     
-    if iterations >= MAX_CPM_ITERATIONS {
-        log("[CPM] Forward pass reached max iterations - possible circular dependency");
+    // No unfinished work is scheduled before the data date
+    final_start = later(final_start, data_date);
+    
+    // Work starts on the task's own calendar
+    let start = calendar.start_on_or_after(final_start?);
+    // Calculate end date (Early Finish)
+    let end = if duration >= 0 {
+        Some(calendar.finish(start, duration))
+    } else {
+        dates[i].end
+    };
+    Some((start, end))
+}
+
+/// Earliest start allowed by a task's predecessor links
+///
+/// `duration` is the work still to be scheduled, which is what FF/SF links
+/// measure back from (the remaining duration for an in-progress task).
+fn predecessor_start(tasks: &[Task], dates: &[Dates], i: usize, duration: i32, calendars: &Calendars, graph: &TaskGraph) -> Option<Moment> {
+    tasks[i].dependencies.iter()
+        .filter_map(|dep| link_start(tasks, dates, i, dep, duration, calendars, graph))
+        .max()
+}
+
+/// Start date a single predecessor link implies for task `i`
+///
+/// None when the link does not take part in scheduling (missing or
+/// unscheduled predecessor, cyclic link, FF/SF into a summary).
+fn link_start(tasks: &[Task], dates: &[Dates], i: usize, dep: &Dependency, duration: i32, calendars: &Calendars, graph: &TaskGraph) -> Option<Moment> {
+    let &pred = graph.index.get(&dep.id)?;
+    // Links inside a circular dependency have no defined order
+    if graph.is_cyclic_link(pred, i) {
+        return None;
+    }
+    // FF/SF links into a summary are not scheduled (as in MS Project):
+    // a summary's finish is the rollup of its children
+    if graph.is_summary(i) && matches!(dep.link_type, LinkType::Ff | LinkType::Sf) {
+        return None;
+    }
+    let (Some(pred_start), Some(pred_end)) = (dates[pred].start, dates[pred].end) else {
+        return None;
+    };
+    
+    // Lag is counted on the lag calendar, the task's own dates on its calendar
+    let calendar = calendars.task(&tasks[i]);
+    let lag_calendar = calendars.lag(&tasks[pred], &tasks[i]);
+    Some(match dep.link_type {
+        LinkType::Ss => calendar.start_on_or_after(lag_calendar.shift(pred_start, dep.lag)),
+        LinkType::Ff => calendar.start_for_finish(lag_calendar.shift(pred_end, dep.lag), duration),
+        LinkType::Sf => calendar.start_for_finish(lag_calendar.shift(pred_start, dep.lag), duration),
+        LinkType::Fs => calendar.start_after(lag_calendar.shift(pred_end, dep.lag)),
+    })
+}
+
+/// Start floor a summary imposes on its children: the later of its own
+/// FS/SS predecessor links, an SNET constraint and its parent's floor
+fn summary_start_floor(tasks: &[Task], dates: &[Dates], p: usize, calendars: &Calendars, graph: &TaskGraph, summary_floor: &[Option<Moment>]) -> Option<Moment> {
+    let mut floor = later(
+        inherited_floor(graph, summary_floor, p),
+        predecessor_start(tasks, dates, p, 0, calendars, graph),
+    );
+    
+    if tasks[p].constraint_type == ConstraintType::Snet {
+        let calendar = calendars.task(&tasks[p]);
+        floor = later(floor, tasks[p].constraint_date.as_deref().filter(|d| !d.is_empty()).and_then(|cd| calendar.as_start(cd)));
     }
+    
+    floor
 }
 
-/// Calculate parent (summary) task dates from children
-pub fn calculate_parent_dates(tasks: &mut [Task], calendar: &Calendar, parent_ids: &HashSet<String>, blank_row_ids: &HashSet<String>) {
-    let max_depth = tasks.iter()
-        .map(|t| get_depth(&t.id, tasks, 0))
+/// Start floor inherited from a task's parent summary
+fn inherited_floor(graph: &TaskGraph, summary_floor: &[Option<Moment>], i: usize) -> Option<Moment> {
+    graph.parent[i].and_then(|p| summary_floor[p])
+}
+
+/// The later of two optional dates
+fn later(a: Option<Moment>, b: Option<Moment>) -> Option<Moment> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// The earlier of two optional dates
+fn earlier(a: Option<Moment>, b: Option<Moment>) -> Option<Moment> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Progress state derived from a task's actuals
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProgressState {
+    NotStarted,
+    InProgress,
+    Complete,
+}
+
+pub(crate) fn progress_state(task: &Task) -> ProgressState {
+    if task.actual_finish.as_deref().is_some_and(|d| !d.is_empty()) {
+        ProgressState::Complete
+    } else if task.actual_start.as_deref().is_some_and(|d| !d.is_empty()) {
+        ProgressState::InProgress
+    } else {
+        ProgressState::NotStarted
+    }
+}
+
+/// Work days left on an in-progress task
+///
+/// Uses `remaining_duration` when set, otherwise derives it from percent complete.
+fn remaining_duration(task: &Task) -> i32 {
+    task.remaining_duration.unwrap_or_else(|| {
+        let progress = task.progress.clamp(0, 100);
+        (task.duration * (100 - progress) + 99) / 100
+    }).max(0)
+}
+
+/// Pin a completed task to its actual dates
+fn schedule_complete(task: &Task, dates: &mut Dates, calendar: WorkCalendar) {
+    let actual_finish = calendar.as_finish(task.actual_finish.as_deref().unwrap_or_default());
+    dates.start = match task.actual_start.as_deref() {
+        Some(actual_start) if !actual_start.is_empty() => calendar.as_start(actual_start),
+        _ => actual_finish.map(|finish| calendar.start_for_finish(finish, task.duration)),
+    };
+    dates.end = actual_finish;
+}
+
+/// Schedule an in-progress task: start at the actual start, finish the
+/// remaining work from the data date on (or later, if logic still drives it)
+fn schedule_in_progress(task: &Task, dates: &mut Dates, remaining: i32, logic_start: Option<Moment>, data_date: Option<Moment>, calendar: WorkCalendar) {
+    let actual_start = calendar.as_start(task.actual_start.as_deref().unwrap_or_default());
+    let resume = later(data_date, logic_start).map(|resume| calendar.start_on_or_after(resume));
+    
+    dates.end = resume.map(|resume| if remaining > 0 {
+        calendar.finish(resume, remaining)
+    } else {
+        // No work left: finished as of the last work day before the data date
+        let last_worked = calendar.finish_before(resume);
+        actual_start.map_or(last_worked, |start| last_worked.max(start))
+    });
+    dates.start = actual_start;
+}
+
+/// Roll up a parent (summary) task's dates from its direct children
+fn roll_up_parent_dates(tasks: &mut [Task], dates: &mut [Dates], parent: usize, calendars: &Calendars, graph: &TaskGraph) {
+    let children = &graph.children[parent];
+    // No scheduled children leaves the summary unscheduled too
+    let start = children.iter().filter_map(|&c| dates[c].start).min();
+    let end = children.iter().filter_map(|&c| dates[c].end).max();
+    dates[parent].start = start;
+    dates[parent].end = end;
+    
+    // Calculate duration from start to end
+    if let (Some(start), Some(end)) = (start, end) {
+        tasks[parent].duration = calendars.task(&tasks[parent]).span(start, end);
+    }
+}
+
+/// Latest Early Finish among leaf tasks (blank rows excluded) - the project finish
+fn project_end(tasks: &[Task], dates: &[Dates], graph: &TaskGraph, parent_ids: &HashSet<String>) -> Option<Moment> {
+    graph.tasks_in_order()
+        .filter(|&i| !parent_ids.contains(&tasks[i].id))
+        .filter_map(|i| dates[i].end)
         .max()
-        .unwrap_or(0);
+}
+
+/// Backward pass - calculate Late Start (LS) and Late Finish (LF)
+///
+/// Walks the forward order in reverse, so every successor's late dates are
+/// final before its predecessors are visited. Only nodes that `changes`
+/// marks for a revisit are recalculated.
+///
+/// A summary's finish step turns its successor logic (and deadline / FNLT)
+/// into a late finish ceiling for all of its children; its start step rolls
+/// the children's late dates back up for the summary's predecessors.
+#[allow(clippy::too_many_arguments)]
+pub fn backward_pass(tasks: &[Task], dates: &mut [Dates], calendars: &Calendars, options: &ScheduleOptions, project_end: Option<Moment>, graph: &TaskGraph, successor_map: &HashMap<String, Vec<SuccessorEntry>>, parent_ids: &HashSet<String>, changes: &mut Changes) {
+    let Some(project_end) = project_end else {
+        return;
+    };
     
-    let task_depths: HashMap<String, i32> = tasks.iter()
-        .map(|t| (t.id.clone(), get_depth(&t.id, tasks, 0)))
-        .collect();
+    let mut summary_ceiling: Vec<Option<Moment>> = vec![None; tasks.len()];
     
-    for depth in (0..=max_depth).rev() {
-        // First, collect children dates for each parent at this depth
-        let mut parent_dates: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
-        
-        for task in tasks.iter() {
-            if !parent_ids.contains(&task.id) {
-                continue;
-            }
-            if task_depths.get(&task.id) != Some(&depth) {
+    for &visit in graph.order.iter().rev() {
+        let node = graph.node(visit);
+        let revisit = changes.revisit_late(graph, node);
+        let i = match visit {
+            Visit::SummaryStart(p) => {
+                if revisit {
+                    let before = (dates[p].late_start, dates[p].late_finish);
+                    roll_up_parent_late_dates(dates, p, graph);
+                    changes.late[node] = before != (dates[p].late_start, dates[p].late_finish);
+                }
                 continue;
             }
-            
-            // Find children (skip blank rows)
-            let mut min_start: Option<String> = None;
-            let mut max_end: Option<String> = None;
-            
-            for child in tasks.iter() {
-                // Skip blank rows
-                if blank_row_ids.contains(&child.id) {
-                    continue;
-                }
-                
-                if child.parent_id.as_ref().map_or(false, |pid| pid == &task.id) {
-                    if !child.start.is_empty() {
-                        if min_start.is_none() || child.start < *min_start.as_ref().unwrap() {
-                            min_start = Some(child.start.clone());
-                        }
-                    }
-                    if !child.end.is_empty() {
-                        if max_end.is_none() || child.end > *max_end.as_ref().unwrap() {
-                            max_end = Some(child.end.clone());
-                        }
-                    }
-                }
+            Visit::Task(i) => i,
+        };
+        let inherited_ceiling = graph.parent[i].and_then(|p| summary_ceiling[p]);
+        
+        // Parent tasks: successors and deadlines cap every child's late finish.
+        // Always derived (cheap, not stored); counts as changed whenever revisited
+        if parent_ids.contains(&tasks[i].id) {
+            let successor_finish = successor_finish(tasks, dates, i, tasks[i].duration, calendars, options, graph, successor_map);
+            let calendar = calendars.task(&tasks[i]);
+            let finish_date = |date: &Option<String>| date.as_deref().filter(|d| !d.is_empty()).and_then(|d| calendar.as_finish(d));
+            let mut ceiling = earlier(inherited_ceiling, successor_finish);
+            ceiling = earlier(ceiling, finish_date(&tasks[i].deadline));
+            if tasks[i].constraint_type == ConstraintType::Fnlt {
+                ceiling = earlier(ceiling, finish_date(&tasks[i].constraint_date));
             }
-            
-            parent_dates.insert(task.id.clone(), (min_start, max_end));
+            summary_ceiling[i] = ceiling;
+            changes.late[node] = revisit;
+            continue;
         }
         
-        // Now apply the dates
-        for task in tasks.iter_mut() {
-            if let Some((min_start, max_end)) = parent_dates.get(&task.id) {
-                if let Some(start) = min_start {
-                    task.start = start.clone();
-                }
-                if let Some(end) = max_end {
-                    task.end = end.clone();
-                }
-                
-                // Calculate duration from start to end
-                if !task.start.is_empty() && !task.end.is_empty() {
-                    task.duration = calc_work_days(&task.start, &task.end, calendar);
-                }
-            }
+        if !revisit {
+            continue;
         }
+        let before = (dates[i].late_start, dates[i].late_finish);
+        schedule_late(tasks, dates, i, calendars, options, project_end, graph, successor_map, inherited_ceiling);
+        changes.late[node] = before != (dates[i].late_start, dates[i].late_finish);
     }
 }
 
-/// Backward pass - calculate Late Start (LS) and Late Finish (LF)
-pub fn backward_pass(tasks: &mut [Task], calendar: &Calendar, successor_map: &HashMap<String, Vec<SuccessorEntry>>, parent_ids: &HashSet<String>, blank_row_ids: &HashSet<String>) {
-    // Find project end date (latest Early Finish among leaf tasks, exclude blank rows)
-    let mut project_end = String::new();
-    for task in tasks.iter() {
-        // Skip blank rows when finding project end
-        if blank_row_ids.contains(&task.id) {
+/// Calculate one leaf task's late dates in the backward pass
+#[allow(clippy::too_many_arguments)]
+fn schedule_late(tasks: &[Task], dates: &mut [Dates], i: usize, calendars: &Calendars, options: &ScheduleOptions, project_end: Moment, graph: &TaskGraph, successor_map: &HashMap<String, Vec<SuccessorEntry>>, inherited_ceiling: Option<Moment>) {
+    // Unscheduled tasks have no late dates either
+    let Some(start) = dates[i].start else {
+        dates[i].late_start = None;
+        dates[i].late_finish = None;
+        return;
+    };
+    
+    // Completed work has no late dates of its own
+    let state = progress_state(&tasks[i]);
+    if state == ProgressState::Complete {
+        dates[i].late_start = Some(start);
+        dates[i].late_finish = dates[i].end;
+        return;
+    }
+    
+    let duration = tasks[i].duration;
+    let work = if state == ProgressState::InProgress { remaining_duration(&tasks[i]) } else { duration };
+    let calendar = calendars.task(&tasks[i]);
+    
+    // No (schedulable) successors - Late Finish = Project End
+    let mut late_finish = successor_finish(tasks, dates, i, duration, calendars, options, graph, successor_map)
+        .unwrap_or(project_end);
+    
+    // Summary successors and deadlines above this task cap it too
+    if let Some(ceiling) = inherited_ceiling {
+        late_finish = late_finish.min(ceiling);
+    }
+    
+    // Deadlines only limit the late finish - they never move the bar
+    if let Some(deadline) = tasks[i].deadline.as_deref().filter(|d| !d.is_empty()).and_then(|d| calendar.as_finish(d)) {
+        late_finish = late_finish.min(deadline);
+    }
+    
+    // Apply constraints to the late dates
+    let constraint = tasks[i].constraint_type;
+    if let Some(cd) = tasks[i].constraint_date.as_deref().filter(|d| !d.is_empty()) {
+        // Start constraints limit the late finish through the task's duration
+        let start_limit = || calendar.as_start(cd).map(|start| calendar.finish(start, work));
+        let limited = match constraint {
+            ConstraintType::Fnlt => calendar.as_finish(cd).map(|cd| late_finish.min(cd)),
+            ConstraintType::Snlt => start_limit().map(|limit| late_finish.min(limit)),
+            ConstraintType::Mfo if state == ProgressState::NotStarted => calendar.as_finish(cd),
+            ConstraintType::Mso if state == ProgressState::NotStarted => start_limit(),
+            _ => None,
+        };
+        if let Some(limited) = limited {
+            late_finish = limited;
+        }
+    }
+    
+    // Calculate Late Start from Late Finish (remaining late start for in-progress work)
+    dates[i].late_start = Some(calendar.start_for_finish(late_finish, work));
+    dates[i].late_finish = Some(late_finish);
+}
+
+/// Latest finish allowed by a task's successor links (None if nothing limits it)
+#[allow(clippy::too_many_arguments)]
+fn successor_finish(tasks: &[Task], dates: &[Dates], i: usize, duration: i32, calendars: &Calendars, options: &ScheduleOptions, graph: &TaskGraph, successor_map: &HashMap<String, Vec<SuccessorEntry>>) -> Option<Moment> {
+    let mut min_late_finish: Option<Moment> = None;
+    
+    for succ in successor_map.get(&tasks[i].id).map(Vec::as_slice).unwrap_or_default() {
+        let Some(&s) = graph.index.get(&succ.id) else {
+            continue;
+        };
+        if graph.is_cyclic_link(i, s) {
             continue;
         }
-        if !parent_ids.contains(&task.id) && !task.end.is_empty() {
-            if project_end.is_empty() || task.end > project_end {
-                project_end = task.end.clone();
-            }
+        // Not scheduled by the forward pass either
+        if graph.is_summary(s) && matches!(succ.link_type, LinkType::Ff | LinkType::Sf) {
+            continue;
         }
+        let succ_task = &tasks[s];
+        let Some(succ_start) = dates[s].start else {
+            continue;
+        };
+        // Started successors no longer wait on this task, unless their
+        // remaining work is still tied to it by retained logic
+        match progress_state(succ_task) {
+            ProgressState::Complete => continue,
+            ProgressState::InProgress if options.progress_mode == ProgressMode::ProgressOverride => continue,
+            _ => {}
+        }
+        
+        let succ_ls = dates[s].late_start.unwrap_or(succ_start);
+        let constrained_finish = finish_limit(succ, duration, succ_ls, &tasks[i], succ_task, calendars);
+        min_late_finish = earlier(min_late_finish, Some(constrained_finish));
     }
     
-    if project_end.is_empty() {
-        return;
-    }
+    min_late_finish
+}
+
+/// Roll up a parent (summary) task's late dates from its direct children
+fn roll_up_parent_late_dates(dates: &mut [Dates], parent: usize, graph: &TaskGraph) {
+    let mut late_start: Option<Moment> = None;
+    let mut late_finish: Option<Moment> = None;
     
-    let mut iterations = 0;
-    let mut changed = true;
+    for &child in &graph.children[parent] {
+        late_start = earlier(late_start, dates[child].late_start);
+        late_finish = later(late_finish, dates[child].late_finish);
+    }
     
-    while changed && iterations < MAX_CPM_ITERATIONS {
-        changed = false;
-        iterations += 1;
+    dates[parent].late_start = late_start;
+    dates[parent].late_finish = late_finish;
+}
+
+/// Record what drove each task's early start, and the relative float of
+/// every incoming link
+///
+/// Relative float is the number of work days a link's predecessor could
+/// slip before it would move this task: 0 means the link is driving.
+/// Summaries are measured against the start floor they pass to their
+/// children; in-progress tasks against the start of their remaining work.
+/// Only tasks whose own or predecessors' early dates changed are analyzed.
+pub fn analyze_driving_logic(tasks: &mut [Task], dates: &[Dates], calendars: &Calendars, graph: &TaskGraph, summary_floor: &[Option<Moment>], data_date: Option<Moment>, changes: &Changes) {
+    for i in graph.tasks_in_order() {
+        if !changes.early_touched(graph, i) {
+            continue;
+        }
+        let state = progress_state(&tasks[i]);
+        let is_summary = graph.is_summary(i);
+        let calendar = calendars.task(&tasks[i]);
+        let manual = tasks[i].scheduling_mode == SchedulingMode::Manual;
         
-        // Build map of current late dates for lookup
-        let late_dates: HashMap<String, (Option<String>, Option<String>)> = tasks.iter()
-            .map(|t| (t.id.clone(), (t.late_start.clone(), t.late_finish.clone())))
-            .collect();
+        // Date the predecessor logic is compared against
+        let logic_date = if is_summary {
+            summary_floor[i].or(dates[i].start)
+        } else if state == ProgressState::InProgress {
+            dates[i].end.map(|end| calendar.start_for_finish(end, remaining_duration(&tasks[i])))
+        } else {
+            dates[i].start
+        };
+        let duration = if state == ProgressState::InProgress { remaining_duration(&tasks[i]) } else { tasks[i].duration };
         
-        let task_data: HashMap<String, (String, String, i32, String, Option<String>)> = tasks.iter()
-            .map(|t| (t.id.clone(), (
-                t.start.clone(), 
-                t.end.clone(), 
-                t.duration,
-                t.constraint_type.clone(),
-                t.constraint_date.clone()
-            )))
+        let links: Vec<LinkAnalysis> = tasks[i].dependencies.iter()
+            .filter_map(|dep| {
+                let implied = link_start(tasks, dates, i, dep, duration, calendars, graph)?;
+                let relative_float = logic_date.map_or(0, |logic_date| calendar.between(implied, logic_date));
+                Some(LinkAnalysis {
+                    predecessor_id: dep.id.clone(),
+                    link_type: dep.link_type,
+                    lag: dep.lag,
+                    relative_float,
+                    driving: relative_float <= 0 && !manual && state != ProgressState::Complete,
+                })
+            })
             .collect();
         
-        for i in 0..tasks.len() {
-            let task_id = tasks[i].id.clone();
-            
-            // Skip blank rows
-            if blank_row_ids.contains(&task_id) {
-                continue;
+        let constraint = tasks[i].constraint_type;
+        let has_constraint_date = tasks[i].constraint_date.as_deref().is_some_and(|d| !d.is_empty());
+        let constraint_start = || {
+            let cd = tasks[i].constraint_date.as_deref().unwrap_or_default();
+            match constraint {
+                ConstraintType::Snet => calendar.as_start(cd).map(|start| calendar.start_on_or_after(start)),
+                ConstraintType::Fnet => calendar.as_finish(cd).map(|finish| calendar.start_on_or_after(calendar.start_for_finish(finish, tasks[i].duration))),
+                _ => None,
             }
-            
-            // Skip parent tasks
-            if parent_ids.contains(&task_id) {
-                continue;
-            }
-            
-            let empty_vec = Vec::new();
-            let successors = successor_map.get(&task_id).unwrap_or(&empty_vec);
-            
-            if successors.is_empty() {
-                // No successors - Late Finish = Project End
-                if tasks[i].late_finish.as_ref() != Some(&project_end) {
-                    tasks[i].late_finish = Some(project_end.clone());
-                    changed = true;
-                }
-            } else {
-                let mut min_late_finish: Option<String> = None;
-                
-                for succ in successors {
-                    if let Some((succ_start, _succ_end, succ_duration, _, _)) = task_data.get(&succ.id) {
-                        if succ_start.is_empty() || parent_ids.contains(&succ.id) {
-                            continue;
-                        }
-                        
-                        let (succ_late_start, _) = late_dates.get(&succ.id).cloned().unwrap_or((None, None));
-                        
-                        let succ_ls = succ_late_start.unwrap_or_else(|| succ_start.clone());
-                        if succ_ls.is_empty() {
-                            continue;
-                        }
-                        
-                        let constrained_finish = match succ.link_type.as_str() {
-                            "FS" => add_work_days(&succ_ls, -1 - succ.lag, calendar),
-                            "SS" => {
-                                let duration = tasks[i].duration;
-                                add_work_days(&succ_ls, get_duration_offset(duration) - succ.lag, calendar)
-                            }
-                            "FF" => add_work_days(&succ_ls, get_duration_offset(*succ_duration) - succ.lag, calendar),
-                            "SF" => add_work_days(&succ_ls, -succ.lag, calendar),
-                            _ => add_work_days(&succ_ls, -1 - succ.lag, calendar),
-                        };
-                        
-                        if min_late_finish.is_none() || constrained_finish < *min_late_finish.as_ref().unwrap() {
-                            min_late_finish = Some(constrained_finish);
-                        }
-                    }
-                }
-                
-                if let Some(lf) = min_late_finish {
-                    if tasks[i].late_finish.as_ref() != Some(&lf) {
-                        tasks[i].late_finish = Some(lf);
-                        changed = true;
-                    }
+        };
+        let inherited = inherited_floor(graph, summary_floor, i);
+        
+        let driven_by = if manual {
+            StartDriver::Manual
+        } else if is_summary {
+            StartDriver::Rollup
+        } else if state == ProgressState::Complete {
+            StartDriver::Actuals
+        } else if matches!(constraint, ConstraintType::Mso | ConstraintType::Mfo) && has_constraint_date && state == ProgressState::NotStarted {
+            StartDriver::Constraint
+        } else if links.iter().any(|l| l.driving) {
+            StartDriver::Predecessor
+        } else if inherited.is_some() && inherited == logic_date {
+            StartDriver::Summary
+        } else if state == ProgressState::NotStarted && has_constraint_date
+            && (constraint == ConstraintType::Alap || constraint_start().is_some_and(|d| Some(d) == logic_date))
+        {
+            StartDriver::Constraint
+        } else if logic_date == data_date {
+            StartDriver::DataDate
+        } else if state == ProgressState::InProgress {
+            StartDriver::Actuals
+        } else {
+            StartDriver::None
+        };
+        
+        tasks[i].driven_by = Some(driven_by);
+        tasks[i].predecessor_links = Some(links);
+    }
+}
+
+/// Walk the driving logic back from a task
+///
+/// Returns the chosen task first, followed by every task that drives it
+/// (breadth-first), each with the link it drives through. A task driven by
+/// its summary continues through the summary's own driving links.
+pub fn driving_path(tasks: &[Task], index: &TaskIndex, task_id: &str) -> Vec<DrivingPathStep> {
+    let Some(start) = index.get(task_id) else {
+        return Vec::new();
+    };
+    
+    let mut path = Vec::new();
+    let mut seen: HashSet<usize> = HashSet::from([start]);
+    let mut queue: VecDeque<(usize, Option<String>, Option<LinkType>)> = VecDeque::from([(start, None, None)]);
+    
+    while let Some((i, successor_id, link_type)) = queue.pop_front() {
+        let task = &tasks[i];
+        path.push(DrivingPathStep {
+            task_id: task.id.clone(),
+            driven_by: task.driven_by,
+            successor_id,
+            link_type,
+        });
+        
+        for link in task.predecessor_links.iter().flatten().filter(|l| l.driving) {
+            if let Some(pred) = index.get(&link.predecessor_id) {
+                if seen.insert(pred) {
+                    queue.push_back((pred, Some(task.id.clone()), Some(link.link_type)));
                 }
             }
-            
-            // Apply FNLT constraint
-            let constraint_type = tasks[i].constraint_type.to_lowercase();
-            if constraint_type == "fnlt" {
-                if let Some(cd) = tasks[i].constraint_date.clone() {
-                    if tasks[i].late_finish.is_none() || cd < *tasks[i].late_finish.as_ref().unwrap() {
-                        tasks[i].late_finish = Some(cd);
-                        changed = true;
-                    }
+        }
+        
+        if task.driven_by == Some(StartDriver::Summary) {
+            if let Some(parent) = index.parent(tasks, i) {
+                if seen.insert(parent) {
+                    queue.push_back((parent, Some(task.id.clone()), None));
                 }
             }
-            
-            // Calculate Late Start from Late Finish
-            if let Some(ref lf) = tasks[i].late_finish {
-                let duration = tasks[i].duration;
-                let new_ls = add_work_days(lf, -get_duration_offset(duration), calendar);
-                if tasks[i].late_start.as_ref() != Some(&new_ls) {
-                    tasks[i].late_start = Some(new_ls);
-                    changed = true;
-                }
+        }
+    }
+    
+    path
+}
+
+/// Latest finish a predecessor can have without moving its successor's start
+///
+/// `succ_start` is the successor's start being protected (late start in the
+/// backward pass, early start when placing ALAP tasks).
+fn finish_limit(succ: &SuccessorEntry, duration: i32, succ_start: Moment, pred_task: &Task, succ_task: &Task, calendars: &Calendars) -> Moment {
+    let calendar = calendars.task(pred_task);
+    let lag_calendar = calendars.lag(pred_task, succ_task);
+    match succ.link_type {
+        LinkType::Ss => {
+            let start = calendar.finish_on_or_before(lag_calendar.shift(succ_start, -succ.lag));
+            calendar.finish(start, duration)
+        }
+        LinkType::Ff => {
+            let succ_end = calendars.task(succ_task).finish(succ_start, succ_task.duration);
+            calendar.finish_on_or_before(lag_calendar.shift(succ_end, -succ.lag))
+        }
+        LinkType::Sf => calendar.finish_on_or_before(lag_calendar.shift(succ_start, -succ.lag)),
+        LinkType::Fs => calendar.finish_before(lag_calendar.shift(succ_start, -succ.lag)),
+    }
+}
+
+/// Place "As Late As Possible" tasks
+///
+/// After the backward pass, each ALAP task is pushed as late as it can go
+/// without delaying any successor's early start or passing its own late
+/// finish. Positions are derived from the task's early dates every time, so
+/// tasks an incremental run did not revisit can move back as well. Visiting
+/// in reverse topological order lets chains of ALAP tasks slide together.
+/// Parent rollups are refreshed afterwards.
+#[allow(clippy::too_many_arguments)]
+pub fn schedule_alap(tasks: &mut [Task], dates: &mut [Dates], calendars: &Calendars, data_date: Option<Moment>, graph: &TaskGraph, successor_map: &HashMap<String, Vec<SuccessorEntry>>, parent_ids: &HashSet<String>, summary_floor: &[Option<Moment>], changes: &mut Changes) {
+    let empty_vec = Vec::new();
+    let mut moved = false;
+    
+    for i in graph.tasks_in_order().rev() {
+        let task = &tasks[i];
+        if parent_ids.contains(&task.id)
+            || task.scheduling_mode == SchedulingMode::Manual
+            || progress_state(task) != ProgressState::NotStarted
+            || task.constraint_type != ConstraintType::Alap
+        {
+            continue;
+        }
+        let Some(mut latest_finish) = dates[i].late_finish else {
+            continue;
+        };
+        let Some((early_start, early_end)) = early_dates(tasks, dates, i, calendars, data_date, graph, summary_floor) else {
+            continue;
+        };
+        
+        for succ in successor_map.get(&task.id).unwrap_or(&empty_vec) {
+            let Some(&s) = graph.index.get(&succ.id) else {
+                continue;
+            };
+            let Some(succ_start) = dates[s].start.filter(|_| !graph.is_cyclic_link(i, s)) else {
+                continue;
+            };
+            if graph.is_summary(s) && matches!(succ.link_type, LinkType::Ff | LinkType::Sf) {
+                continue;
             }
+            let limit = finish_limit(succ, task.duration, succ_start, task, &tasks[s], calendars);
+            latest_finish = latest_finish.min(limit);
+        }
+        
+        let (start, end) = if early_end.is_none_or(|early_end| latest_finish > early_end) {
+            (Some(calendars.task(task).start_for_finish(latest_finish, task.duration)), Some(latest_finish))
+        } else {
+            (Some(early_start), early_end)
+        };
+        if (start, end) != (dates[i].start, dates[i].end) {
+            dates[i].start = start;
+            dates[i].end = end;
+            changes.early[i] = true;
+            moved = true;
         }
     }
     
-    if iterations >= MAX_CPM_ITERATIONS {
-        log("[CPM] Backward pass reached max iterations - possible circular dependency");
+    if moved {
+        for i in graph.tasks_in_order() {
+            if parent_ids.contains(&tasks[i].id) {
+                let before = (dates[i].start, dates[i].end);
+                roll_up_parent_dates(tasks, dates, i, calendars, graph);
+                if before != (dates[i].start, dates[i].end) {
+                    changes.early[i] = true;
+                }
+            }
+        }
     }
 }
 
 /// Calculate Total Float and Free Float for all tasks
-pub fn calculate_float(tasks: &mut [Task], calendar: &Calendar, successor_map: &HashMap<String, Vec<SuccessorEntry>>, parent_ids: &HashSet<String>, blank_row_ids: &HashSet<String>) {
+///
+/// Leaf tasks are only recalculated where `changes` says their float can
+/// have moved; parent floats are always refreshed from their children.
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_float(tasks: &mut [Task], dates: &[Dates], calendars: &Calendars, graph: &TaskGraph, successor_map: &HashMap<String, Vec<SuccessorEntry>>, parent_ids: &HashSet<String>, blank_row_ids: &HashSet<String>, index: &TaskIndex, rollup_order: &[usize], changes: &Changes) {
     // First pass: calculate float for leaf tasks
-    // Collect task data for lookup
-    let task_data: HashMap<String, (String, String, bool)> = tasks.iter()
-        .map(|t| (t.id.clone(), (t.start.clone(), t.end.clone(), parent_ids.contains(&t.id))))
-        .collect();
-    
     for i in 0..tasks.len() {
-        let task_id = tasks[i].id.clone();
+        if !changes.float_touched(graph, i) {
+            continue;
+        }
+        let task = &tasks[i];
+        let calendar = calendars.task(task);
         
         // Skip blank rows
-        if blank_row_ids.contains(&task_id) {
-            tasks[i].total_float_days = None;
-            tasks[i].total_float = None;
-            tasks[i].free_float_days = None;
-            tasks[i].free_float = None;
+        if blank_row_ids.contains(&task.id) {
+            let task = &mut tasks[i];
+            task.total_float_days = None;
+            task.total_float = None;
+            task.free_float_days = None;
+            task.free_float = None;
             continue;
         }
         
-        if parent_ids.contains(&task_id) {
+        if parent_ids.contains(&task.id) {
             // Parent tasks: will calculate from children in second pass
             continue;
         }
         
-        // Total Float = Late Start - Early Start (in work days)
-        if let (Some(ref ls), ref start) = (&tasks[i].late_start, &tasks[i].start) {
-            if !start.is_empty() {
-                tasks[i].total_float_days = Some(calc_work_days_difference(start, ls, calendar));
-            } else {
-                tasks[i].total_float_days = Some(0);
-            }
-        } else {
-            tasks[i].total_float_days = Some(0);
-        }
-        tasks[i].total_float = tasks[i].total_float_days.map(|v| v as f64);
+        // Total Float = Late Start - Early Start (in work days).
+        // Started tasks measure it at the finish, since their start is history.
+        // Unscheduled tasks have no float (and so are never critical)
+        let Dates { start, end, late_start, late_finish } = dates[i];
+        let total_float_days = match start {
+            None => None,
+            Some(_) if progress_state(task) != ProgressState::NotStarted => Some(match (end, late_finish) {
+                (Some(end), Some(lf)) => calendar.between(end, lf),
+                _ => 0,
+            }),
+            Some(start) => Some(late_start.map_or(0, |ls| calendar.between(start, ls))),
+        };
         
         // Free Float calculation
         let empty_vec = Vec::new();
-        let successors = successor_map.get(&task_id).unwrap_or(&empty_vec);
-        
-        if successors.is_empty() {
-            // No successors - free float equals total float
-            tasks[i].free_float_days = tasks[i].total_float_days;
-            tasks[i].free_float = tasks[i].total_float;
-        } else {
-            let mut min_free_float: Option<i32> = None;
-            
-            for succ in successors {
-                if let Some((succ_start, succ_end, is_parent)) = task_data.get(&succ.id) {
-                    if succ_start.is_empty() || *is_parent {
-                        continue;
-                    }
-                    
-                    let lag = succ.lag;
-                    let task_start = &tasks[i].start;
-                    let task_end = &tasks[i].end;
-                    
-                    let free_float_for_succ = match succ.link_type.as_str() {
-                        "FS" => calc_work_days_difference(task_end, succ_start, calendar) - 1 - lag,
-                        "SS" => calc_work_days_difference(task_start, succ_start, calendar) - lag,
-                        "FF" => calc_work_days_difference(task_end, succ_end, calendar) - lag,
-                        "SF" => calc_work_days_difference(task_start, succ_end, calendar) - lag,
-                        _ => calc_work_days_difference(task_end, succ_start, calendar) - 1 - lag,
-                    };
-                    
-                    if min_free_float.is_none() || free_float_for_succ < min_free_float.unwrap() {
-                        min_free_float = Some(free_float_for_succ);
-                    }
-                }
-            }
-            
-            // Free float cannot exceed total float
-            let total_float_val = tasks[i].total_float_days.unwrap_or(0);
-            tasks[i].free_float_days = min_free_float.map(|mff| {
-                (mff.max(0)).min(total_float_val)
-            }).or(Some(total_float_val));
-            tasks[i].free_float = tasks[i].free_float_days.map(|v| v as f64);
-        }
-    }
-    
-    // Second pass: calculate parent task floats from children
-    let max_depth = tasks.iter()
-        .map(|t| get_depth(&t.id, tasks, 0))
-        .max()
-        .unwrap_or(0);
-    
-    let task_depths: HashMap<String, i32> = tasks.iter()
-        .map(|t| (t.id.clone(), get_depth(&t.id, tasks, 0)))
-        .collect();
-    
-    for depth in (0..=max_depth).rev() {
-        // Collect child floats for each parent at this depth
-        let mut parent_floats: HashMap<String, Option<i32>> = HashMap::new();
+        let successors = successor_map.get(&task.id).unwrap_or(&empty_vec);
+        let mut min_free_float: Option<i32> = None;
         
-        for task in tasks.iter() {
-            if !parent_ids.contains(&task.id) {
+        for succ in successors {
+            let Some(&s) = graph.index.get(&succ.id) else {
                 continue;
-            }
-            if task_depths.get(&task.id) != Some(&depth) {
+            };
+            let Some(succ_start) = dates[s].start.filter(|_| !parent_ids.contains(&succ.id)) else {
                 continue;
-            }
+            };
             
-            let mut child_floats: Vec<i32> = Vec::new();
-            for child in tasks.iter() {
-                // Skip blank rows
-                if blank_row_ids.contains(&child.id) {
-                    continue;
-                }
-                
-                if child.parent_id.as_ref().map_or(false, |pid| pid == &task.id) {
-                    if let Some(tf) = child.total_float_days {
-                        child_floats.push(tf);
-                    }
-                }
-            }
+            // Successor dates taken back over the lag, measured on this task's calendar
+            let lag_calendar = calendars.lag_between(calendar, calendars.task(&tasks[s]));
+            let unlagged = |date: Moment| lag_calendar.shift(date, -succ.lag);
+            // Missing dates count as no float
+            let between = |from: Option<Moment>, to: Option<Moment>| match (from, to) {
+                (Some(from), Some(to)) => calendar.between(from, to),
+                _ => 0,
+            };
+            let succ_end = || dates[s].end.map(unlagged);
             
-            let min_float = if child_floats.is_empty() {
-                Some(0)
-            } else {
-                child_floats.iter().min().copied()
+            let free_float_for_succ = match succ.link_type {
+                LinkType::Ss => between(start, Some(unlagged(succ_start))),
+                LinkType::Ff => between(end, succ_end()),
+                LinkType::Sf => between(start, succ_end()),
+                LinkType::Fs => between(end, Some(calendar.finish_before(unlagged(succ_start)))),
             };
             
-            parent_floats.insert(task.id.clone(), min_float);
+            min_free_float = Some(min_free_float.map_or(free_float_for_succ, |mff| mff.min(free_float_for_succ)));
         }
         
-        // Apply the floats
-        for task in tasks.iter_mut() {
-            if let Some(min_float) = parent_floats.get(&task.id) {
-                task.total_float_days = *min_float;
-                task.total_float = task.total_float_days.map(|v| v as f64);
-                task.free_float_days = Some(0);
-                task.free_float = Some(0.0);
+        let task = &mut tasks[i];
+        task.total_float_days = total_float_days;
+        task.total_float = task.total_float_days.map(|v| v as f64);
+        // No successors - free float equals total float.
+        // Free float cannot exceed total float
+        task.free_float_days = task.total_float_days.map(|total_float| {
+            min_free_float.map_or(total_float, |mff| mff.max(0).min(total_float))
+        });
+        task.free_float = task.free_float_days.map(|v| v as f64);
+    }
+    
+    // Second pass: calculate parent task floats from children, deepest
+    // parents first so nested summaries are final before their parents
+    for &p in rollup_order {
+        let min_float = index.children(&tasks[p].id).iter()
+            .map(|&c| &tasks[c])
+            .filter(|c| !blank_row_ids.contains(&c.id)) // Skip blank rows
+            .filter_map(|c| c.total_float_days)
+            .min()
+            .or(Some(0));
+        
+        let task = &mut tasks[p];
+        task.total_float_days = min_float;
+        task.total_float = task.total_float_days.map(|v| v as f64);
+        task.free_float_days = Some(0);
+        task.free_float = Some(0.0);
+    }
+}
+
+/// Calculate each task's deadline variance in work days
+///
+/// Positive = finishes ahead of the deadline, negative = misses it.
+/// Returns the number of tasks that miss their deadline.
+pub fn calculate_deadline_variance(tasks: &mut [Task], dates: &[Dates], calendars: &Calendars, blank_row_ids: &HashSet<String>) -> i32 {
+    let mut missed = 0;
+    
+    for (task, dates) in tasks.iter_mut().zip(dates) {
+        let calendar = calendars.task(task);
+        task.deadline_variance = match (task.deadline.as_deref(), dates.end) {
+            (Some(deadline), Some(end)) if !blank_row_ids.contains(&task.id) => {
+                calendar.as_finish(deadline).map(|deadline| calendar.between(end, deadline))
             }
+            _ => None,
+        };
+        if task.deadline_variance.is_some_and(|v| v < 0) {
+            missed += 1;
         }
     }
+    
+    missed
 }
 
-/// Mark critical path based on Total Float
-pub fn mark_critical_path(tasks: &mut [Task], parent_ids: &HashSet<String>, blank_row_ids: &HashSet<String>) {
-    // First pass: mark leaf tasks based on float
-    for task in tasks.iter_mut() {
-        // Blank rows are never critical
-        if blank_row_ids.contains(&task.id) {
+/// Mark critical and near-critical tasks
+///
+/// Critical is either "total float <= threshold" or "on the longest path",
+/// depending on `options.critical_path`. Non-critical tasks with total float
+/// within `options.near_critical_threshold` are near-critical.
+#[allow(clippy::too_many_arguments)]
+pub fn mark_critical_path(tasks: &mut [Task], dates: &[Dates], options: &ScheduleOptions, graph: &TaskGraph, parent_ids: &HashSet<String>, blank_row_ids: &HashSet<String>, index: &TaskIndex, rollup_order: &[usize]) {
+    let longest_path = match options.critical_path {
+        CriticalPathMode::LongestPath => Some(longest_path_tasks(tasks, dates, graph, parent_ids)),
+        CriticalPathMode::TotalFloat => None,
+    };
+    
+    // First pass: mark leaf tasks
+    for (i, task) in tasks.iter_mut().enumerate() {
+        // Blank rows are never critical; parents are set in the second pass
+        if blank_row_ids.contains(&task.id) || parent_ids.contains(&task.id) {
             task.is_critical = Some(false);
+            task.is_near_critical = Some(false);
             continue;
         }
         
-        if parent_ids.contains(&task.id) {
-            task.is_critical = Some(false); // Will be set in second pass
-        } else {
-            task.is_critical = Some(task.total_float_days.map_or(false, |tf| tf <= 0));
-        }
+        // Completed work can no longer delay the project
+        let active = progress_state(task) != ProgressState::Complete;
+        let total_float = task.total_float_days;
+        let critical = active && match &longest_path {
+            Some(path) => path.contains(&i),
+            None => total_float.is_some_and(|tf| tf <= options.critical_float_threshold),
+        };
+        task.is_critical = Some(critical);
+        task.is_near_critical = Some(
+            active && !critical && total_float.is_some_and(|tf| tf <= options.near_critical_threshold)
+        );
     }
     
-    // Second pass: mark parent tasks as critical if any child is critical
-    let max_depth = tasks.iter()
-        .map(|t| get_depth(&t.id, tasks, 0))
-        .max()
-        .unwrap_or(0);
+    // Second pass: a parent is critical if any child is critical,
+    // otherwise near-critical if any child is near-critical (deepest first)
+    for &p in rollup_order {
+        let children = || index.children(&tasks[p].id).iter()
+            .map(|&c| &tasks[c])
+            .filter(|c| !blank_row_ids.contains(&c.id)); // Skip blank rows
+        let has_critical_child = children().any(|c| c.is_critical.unwrap_or(false));
+        let has_near_critical_child = children().any(|c| c.is_near_critical.unwrap_or(false));
+        
+        tasks[p].is_critical = Some(has_critical_child);
+        tasks[p].is_near_critical = Some(has_near_critical_child && !has_critical_child);
+    }
+}
+
+/// Leaf tasks on the longest path: the driving chains that end at the
+/// project finish
+///
+/// Walks back from every leaf that finishes on the project end date through
+/// driving links. A summary reached as a predecessor continues through the
+/// children that set its finish; a task driven by its summary continues
+/// through the summary's own driving links.
+fn longest_path_tasks(tasks: &[Task], dates: &[Dates], graph: &TaskGraph, parent_ids: &HashSet<String>) -> HashSet<usize> {
+    let is_leaf = |i: usize| !parent_ids.contains(&tasks[i].id);
+    let mut on_path: HashSet<usize> = HashSet::new();
+    let Some(project_end) = project_end(tasks, dates, graph, parent_ids) else {
+        return on_path;
+    };
     
-    let task_depths: HashMap<String, i32> = tasks.iter()
-        .map(|t| (t.id.clone(), get_depth(&t.id, tasks, 0)))
+    let mut seen: HashSet<usize> = HashSet::new();
+    let mut stack: Vec<usize> = graph.tasks_in_order()
+        .filter(|&i| is_leaf(i) && dates[i].end == Some(project_end))
         .collect();
     
-    for depth in (0..=max_depth).rev() {
-        // Collect critical status for parents at this depth
-        let mut parent_critical: HashMap<String, bool> = HashMap::new();
-        
-        for task in tasks.iter() {
-            if !parent_ids.contains(&task.id) {
-                continue;
-            }
-            if task_depths.get(&task.id) != Some(&depth) {
-                continue;
-            }
-            
-            let has_critical_child = tasks.iter()
-                .filter(|c| !blank_row_ids.contains(&c.id)) // Skip blank rows
-                .filter(|c| c.parent_id.as_ref().map_or(false, |pid| pid == &task.id))
-                .any(|c| c.is_critical.unwrap_or(false));
-            
-            parent_critical.insert(task.id.clone(), has_critical_child);
+    while let Some(i) = stack.pop() {
+        if !seen.insert(i) {
+            continue;
         }
+        let task = &tasks[i];
         
-        // Apply critical status
-        for task in tasks.iter_mut() {
-            if let Some(&is_crit) = parent_critical.get(&task.id) {
-                task.is_critical = Some(is_crit);
+        if graph.is_summary(i) {
+            // Reached as a predecessor: its finish comes from its last children
+            stack.extend(graph.children[i].iter().copied().filter(|&c| dates[c].end == dates[i].end));
+        } else {
+            on_path.insert(i);
+            if task.driven_by == Some(StartDriver::Summary) {
+                if let Some(p) = graph.parent[i] {
+                    stack.extend(driving_predecessors(tasks, p, graph));
+                }
             }
         }
+        stack.extend(driving_predecessors(tasks, i, graph));
+    }
+    
+    on_path
+}
+
+/// Indices of the predecessors on a task's driving links
+fn driving_predecessors<'a>(tasks: &'a [Task], i: usize, graph: &'a TaskGraph) -> impl Iterator<Item = usize> + 'a {
+    tasks[i].predecessor_links.iter()
+        .flatten()
+        .filter(|l| l.driving)
+        .filter_map(|l| graph.index.get(&l.predecessor_id).copied())
+}
+
+/// Build the stats error message for circular dependencies, e.g.
+/// "Circular dependency detected: A -> B -> A"
+fn describe_cycles(cycles: &[DependencyCycle]) -> Option<String> {
+    let first = cycles.first()?;
+    let mut chain = first.task_ids.clone();
+    chain.push(first.task_ids[0].clone());
+    
+    let mut message = format!("Circular dependency detected: {}", chain.join(" -> "));
+    if cycles.len() > 1 {
+        message.push_str(&format!(" (and {} more)", cycles.len() - 1));
     }
+    Some(message)
 }
 
 // Import the log function from lib.rs
 use crate::log;
 
+/// Resolve the data date used for a calculation
+///
+/// An explicit data date always wins. Without one, the project start is the
+/// earliest start entered on a task (actual start, or an SNET/MSO date), and
+/// failing that `opened_on`. Finish dates and upper limits (FNLT, MFO, SNLT)
+/// say nothing about when work can begin, and dates the calculation wrote
+/// itself would keep a task where it was, so neither is used.
+fn resolve_data_date(tasks: &[Task], data_date: Option<&str>, opened_on: Option<&str>, blank_row_ids: &HashSet<String>) -> Option<String> {
+    if let Some(date) = data_date.filter(|d| !d.is_empty()) {
+        return Some(date.to_string());
+    }
+    
+    tasks.iter()
+        .filter(|t| !blank_row_ids.contains(&t.id))
+        .flat_map(|t| {
+            let constraint_date = t.constraint_date.as_deref()
+                .filter(|_| matches!(t.constraint_type, ConstraintType::Snet | ConstraintType::Mso));
+            [t.actual_start.as_deref(), constraint_date]
+        })
+        .flatten()
+        .filter(|d| !d.is_empty())
+        .min()
+        .or(opened_on.filter(|d| !d.is_empty()))
+        .map(|d| d.to_string())
+}
+
 /// Main CPM calculation function
-pub fn calculate(tasks: &mut [Task], calendar: &Calendar) -> CPMResult {
-    // Use js_sys::Date for WASM-compatible timing (std::time::Instant not supported in WASM)
-    let start_time = Date::now();
+///
+/// `data_date` is the project status date: no unfinished work is scheduled
+/// before it, and in-progress work resumes from it. Without one the project
+/// starts at the earliest entered start, or else at `opened_on` (see
+/// `resolve_data_date`). Each task is counted on its own calendar from
+/// `calendars`.
+///
+/// With `dirty` set to the indices of the tasks edited since the last
+/// calculation, only what those edits can reach is recalculated and the
+/// result holds just the tasks whose values changed. With `None` every task
+/// is recalculated and returned.
+#[allow(clippy::too_many_arguments)]
+pub fn calculate(tasks: &mut [Task], calendars: &Calendars, options: &ScheduleOptions, data_date: Option<&str>, opened_on: Option<&str>, network: &mut Network, index: &TaskIndex, dirty: Option<&[usize]>) -> CPMResult {
+    let start_time = now_ms();
     
     if tasks.is_empty() {
         return CPMResult {
             tasks: Vec::new(),
-            stats: CPMStats {
-                calc_time: 0.0,
-                task_count: 0,
-                critical_count: 0,
-                project_end: String::new(),
-                duration: 0,
-                error: None,
-            },
+            stats: CPMStats::default(),
+            cycles: Vec::new(),
+            full: true,
         };
     }
     
-    // O(N) pre-computation - build lookup sets ONCE
-    let parent_ids: HashSet<String> = tasks.iter()
-        .filter_map(|t| t.parent_id.as_ref())
-        .cloned()
-        .collect();
+    let Network { parent_ids, blank_row_ids, successor_map, graph, rollup_order, project_end: previous_end, data_start: previous_start } = network;
     
-    // Build set of blank row IDs to skip in CPM
-    let blank_row_ids: HashSet<String> = tasks.iter()
-        .filter(|t| t.row_type.as_deref() == Some("blank"))
-        .map(|t| t.id.clone())
-        .collect();
+    let mut errors: Vec<String> = Vec::new();
+    let project = calendars.project();
+    let resolved_data_date = resolve_data_date(tasks, data_date, opened_on, blank_row_ids);
+    let data_start = resolved_data_date.as_deref()
+        .and_then(|d| project.as_start(d))
+        .map(|d| project.start_on_or_after(d));
+    if resolved_data_date.is_none() {
+        errors.push("No data date set and no task has an actual start or start constraint to fall back on".to_string());
+    }
     
-    // Step 1: Build successor map for backward pass
-    let successor_map = build_successor_map(tasks, &blank_row_ids);
+    // Step 1: Work out what to revisit. A new data date (an explicit one, or
+    // a project start that moved) can move any task
+    let mut changes = match dirty {
+        Some(dirty) if data_start == *previous_start => Changes::from_dirty(graph, dirty),
+        _ => Changes::all(graph),
+    };
+    *previous_start = data_start;
+    let before: Vec<u64> = match dirty {
+        Some(_) => tasks.iter().map(fingerprint).collect(),
+        None => Vec::new(),
+    };
+    let read: Vec<Dates> = tasks.iter().map(|t| Dates::read(t, calendars.task(t))).collect();
+    let mut dates = read.clone();
     
-    // Step 2: Forward pass - calculate Early Start and Early Finish
-    forward_pass(tasks, calendar, &parent_ids, &blank_row_ids);
+    // Flag tasks on circular dependencies so the UI can mark them "Blocked".
+    // An edit anywhere in the network can open or close a cycle, so tasks on
+    // one (before or after) are always revisited
+    for (i, task) in tasks.iter_mut().enumerate() {
+        let in_cycle = if blank_row_ids.contains(&task.id) {
+            None
+        } else {
+            Some(graph.is_in_cycle(i))
+        };
+        if task.in_cycle == Some(true) || in_cycle == Some(true) {
+            changes.mark_dirty(graph, i);
+        }
+        task.in_cycle = in_cycle;
+    }
+    if let Some(message) = describe_cycles(&graph.cycles) {
+        log(&format!("[CPM] {}", message));
+        errors.push(message);
+    }
     
-    // Step 3: Calculate parent dates from children
-    calculate_parent_dates(tasks, calendar, &parent_ids, &blank_row_ids);
+    // Step 2: Forward pass - calculate Early Start and Early Finish,
+    // rolling up parent dates as soon as their children are scheduled
+    let summary_floor = forward_pass(tasks, &mut dates, calendars, options, data_start, graph, parent_ids, &mut changes);
     
-    // Step 4: Backward pass - calculate Late Start and Late Finish
-    backward_pass(tasks, calendar, &successor_map, &parent_ids, &blank_row_ids);
+    // Step 3: Backward pass - calculate Late Start and Late Finish.
+    // A new project finish moves the late dates of every open-ended chain
+    let late_end = project_end(tasks, &dates, graph, parent_ids);
+    if late_end != *previous_end {
+        changes.all_late = true;
+        *previous_end = late_end;
+    }
+    backward_pass(tasks, &mut dates, calendars, options, late_end, graph, successor_map, parent_ids, &mut changes);
     
-    // Step 5: Calculate float values
-    calculate_float(tasks, calendar, &successor_map, &parent_ids, &blank_row_ids);
+    // Step 4: Push ALAP tasks as late as their successors allow
+    schedule_alap(tasks, &mut dates, calendars, data_start, graph, successor_map, parent_ids, &summary_floor, &mut changes);
     
-    // Step 6: Mark critical path based on float
-    mark_critical_path(tasks, &parent_ids, &blank_row_ids);
+    // Step 5: Record driving predecessors and relative float per link
+    analyze_driving_logic(tasks, &dates, calendars, graph, &summary_floor, data_start, &changes);
     
-    // Calculate elapsed time in milliseconds (Date::now() returns ms since epoch)
-    let calc_time = Date::now() - start_time;
+    // Step 6: Calculate float values
+    calculate_float(tasks, &dates, calendars, graph, successor_map, parent_ids, blank_row_ids, index, rollup_order, &changes);
     
-    // Find project end date (exclude blank rows)
-    let valid_ends: Vec<String> = tasks.iter()
-        .filter(|t| !blank_row_ids.contains(&t.id))
-        .filter(|t| !t.end.is_empty() && !parent_ids.contains(&t.id))
-        .map(|t| t.end.clone())
-        .collect();
+    // Step 7: Mark critical / near-critical tasks
+    mark_critical_path(tasks, &dates, options, graph, parent_ids, blank_row_ids, index, rollup_order);
     
-    let mut sorted_ends = valid_ends;
-    sorted_ends.sort();
-    sorted_ends.reverse();
-    let project_end = sorted_ends.first().cloned().unwrap_or_default();
+    // Step 8: Rate each task's schedule health
+    assess_health(tasks, &dates, calendars, options, graph, blank_row_ids);
     
-    // Calculate project duration in work days (exclude blank rows)
-    let leaf_tasks: Vec<&Task> = tasks.iter()
-        .filter(|t| !blank_row_ids.contains(&t.id))
-        .filter(|t| !t.start.is_empty() && !parent_ids.contains(&t.id))
-        .collect();
+    // Step 9: Compare finishes against deadlines
+    let missed_deadline_count = calculate_deadline_variance(tasks, &dates, calendars, blank_row_ids);
     
-    let mut starts: Vec<String> = leaf_tasks.iter()
-        .map(|t| t.start.clone())
-        .collect();
-    starts.sort();
+    // Write the dates that moved back to the tasks
+    for (i, task) in tasks.iter_mut().enumerate() {
+        dates[i].write(&read[i], task, calendars.task(task));
+    }
     
-    let project_start = starts.first().cloned().unwrap_or_default();
-    let duration = if !project_start.is_empty() && !project_end.is_empty() {
-        calc_work_days(&project_start, &project_end, calendar)
-    } else {
-        0
+    // A date search that ran out of working time left its dates unmoved
+    if let Some(date_error) = calendars.take_error() {
+        log(&format!("[CPM] {}", date_error));
+        errors.push(date_error.to_string());
+    }
+    
+    // Calculate elapsed time in milliseconds
+    let calc_time = now_ms() - start_time;
+    
+    // Project start and end from the leaf tasks (exclude blank rows)
+    let leaf_dates = || (0..tasks.len())
+        .filter(|&i| !blank_row_ids.contains(&tasks[i].id) && !parent_ids.contains(&tasks[i].id))
+        .map(|i| dates[i]);
+    let project_start = leaf_dates().filter_map(|d| d.start).min();
+    let project_end = leaf_dates().filter_map(|d| d.end).max();
+    
+    // Calculate project duration in work days
+    let duration = match (project_start, project_end) {
+        (Some(start), Some(end)) => project.span(start, end),
+        _ => 0,
     };
+    let project_end = project_end.map(|end| project.format(end)).unwrap_or_default();
     
-    let critical_count = tasks.iter()
-        .filter(|t| !blank_row_ids.contains(&t.id))
-        .filter(|t| t.is_critical.unwrap_or(false) && !parent_ids.contains(&t.id))
-        .count();
+    let leaves: Vec<&Task> = tasks.iter()
+        .filter(|t| !blank_row_ids.contains(&t.id) && !parent_ids.contains(&t.id))
+        .collect();
+    let critical_count = leaves.iter().filter(|t| t.is_critical.unwrap_or(false)).count();
+    let near_critical_count = leaves.iter().filter(|t| t.is_near_critical.unwrap_or(false)).count();
+    let non_critical_count = leaves.len() - critical_count - near_critical_count;
+    
+    // Return every task after a full calculation, otherwise the edited
+    // tasks and any task a pass changed
+    let (changed, full) = match dirty {
+        Some(dirty) => {
+            let dirty: HashSet<usize> = dirty.iter().copied().collect();
+            let changed = tasks.iter()
+                .enumerate()
+                .filter(|(i, t)| dirty.contains(i) || fingerprint(t) != before[*i])
+                .map(|(_, t)| t.clone())
+                .collect();
+            (changed, false)
+        }
+        None => (tasks.to_vec(), true),
+    };
     
     CPMResult {
-        tasks: tasks.to_vec(),
+        tasks: changed,
         stats: CPMStats {
             calc_time,
             task_count: tasks.len() as i32,
             critical_count: critical_count as i32,
+            near_critical_count: near_critical_count as i32,
+            non_critical_count: non_critical_count as i32,
+            missed_deadline_count,
             project_end,
             duration,
-            error: None,
+            error: if errors.is_empty() { None } else { Some(errors.join("; ")) },
         },
+        cycles: graph.cycles.clone(),
+        full,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{after, child, task};
+    use crate::types::Calendar;
+    
+    /// Monday to Friday
+    fn weekdays() -> Calendar {
+        Calendar { working_days: vec![1, 2, 3, 4, 5], ..Calendar::default() }
+    }
+    
+    /// Calculate `tasks` with the default options, keeping `network` between runs
+    fn run(tasks: &mut [Task], network: &mut Network, data_date: Option<&str>, dirty: Option<&[usize]>) -> CPMResult {
+        run_opened(tasks, network, data_date, None, dirty)
+    }
+    
+    fn run_opened(tasks: &mut [Task], network: &mut Network, data_date: Option<&str>, opened_on: Option<&str>, dirty: Option<&[usize]>) -> CPMResult {
+        let calendar = weekdays();
+        let named = HashMap::new();
+        let options = ScheduleOptions::default();
+        let calendars = Calendars::new(&calendar, &named, None, &options);
+        let index = TaskIndex::build(tasks);
+        calculate(tasks, &calendars, &options, data_date, opened_on, network, &index, dirty)
+    }
+    
+    fn calculated(mut tasks: Vec<Task>, data_date: Option<&str>) -> (Vec<Task>, CPMResult) {
+        let mut network = Network::build(&tasks, &TaskIndex::build(&tasks));
+        let result = run(&mut tasks, &mut network, data_date, None);
+        (tasks, result)
+    }
+    
+    fn snet(id: &str, date: &str) -> Task {
+        Task {
+            constraint_type: ConstraintType::Snet,
+            constraint_date: Some(date.to_string()),
+            ..task(id, 2)
+        }
+    }
+    
+    fn started(id: &str, date: &str) -> Task {
+        Task { actual_start: Some(date.to_string()), ..task(id, 3) }
+    }
+    
+    #[test]
+    fn explicit_data_date_comes_first() {
+        let (tasks, _) = calculated(vec![snet("A", "2024-01-08"), task("B", 1)], Some("2024-01-17"));
+        
+        assert_eq!(tasks[0].start, "2024-01-17");
+        assert_eq!(tasks[1].start, "2024-01-17");
+    }
+    
+    #[test]
+    fn without_a_data_date_the_project_starts_at_the_earliest_entered_date() {
+        let (tasks, result) = calculated(
+            vec![task("A", 1), started("B", "2024-01-10"), snet("C", "2024-01-15")],
+            None,
+        );
+        
+        assert_eq!(tasks[0].start, "2024-01-10");
+        assert_eq!(tasks[2].start, "2024-01-15");
+        assert_eq!(result.stats.error, None);
+    }
+    
+    #[test]
+    fn finish_dates_and_upper_limits_do_not_set_the_project_start() {
+        let limit = |id: &str, constraint_type| Task {
+            constraint_type,
+            constraint_date: Some("2025-12-31".to_string()),
+            ..task(id, 2)
+        };
+        let tasks = vec![limit("A", ConstraintType::Fnlt), limit("B", ConstraintType::Mfo), limit("C", ConstraintType::Snlt)];
+        
+        // Only the mandatory finish is placed, on its own date
+        let (unscheduled, result) = calculated(tasks.clone(), None);
+        assert_eq!((unscheduled[0].start.as_str(), unscheduled[2].start.as_str()), ("", ""));
+        assert_eq!(unscheduled[1].end, "2025-12-31");
+        assert!(result.stats.error.is_some());
+        
+        let mut tasks = tasks;
+        let mut network = Network::build(&tasks, &TaskIndex::build(&tasks));
+        let result = run_opened(&mut tasks, &mut network, None, Some("2024-01-08"), None);
+        assert_eq!(tasks[0].start, "2024-01-08");
+        assert_eq!(tasks[2].start, "2024-01-08");
+        assert_eq!(result.stats.error, None);
+    }
+    
+    #[test]
+    fn an_entered_start_comes_before_the_day_the_project_was_opened() {
+        let mut tasks = vec![task("A", 1), snet("B", "2024-01-15")];
+        let mut network = Network::build(&tasks, &TaskIndex::build(&tasks));
+        run_opened(&mut tasks, &mut network, None, Some("2024-01-08"), None);
+        
+        assert_eq!(tasks[0].start, "2024-01-15");
+    }
+    
+    #[test]
+    fn removing_a_constraint_moves_the_task_back_to_the_project_start() {
+        let mut tasks = vec![snet("A", "2024-01-08"), started("B", "2024-01-10"), after(task("C", 1), &["A"])];
+        let mut network = Network::build(&tasks, &TaskIndex::build(&tasks));
+        run(&mut tasks, &mut network, None, None);
+        assert_eq!(tasks[0].start, "2024-01-08");
+        
+        tasks[0].constraint_type = ConstraintType::Asap;
+        tasks[0].constraint_date = None;
+        let result = run(&mut tasks, &mut network, None, Some(&[0]));
+        
+        assert_eq!(tasks[0].start, "2024-01-10");
+        assert_eq!(tasks[0].end, "2024-01-11");
+        assert_eq!(tasks[2].start, "2024-01-12");
+        assert!(result.tasks.iter().any(|t| t.id == "C"));
+    }
+    
+    #[test]
+    fn without_entered_dates_the_project_starts_the_day_it_was_opened() {
+        let mut tasks = vec![task("A", 2), after(task("B", 1), &["A"])];
+        let mut network = Network::build(&tasks, &TaskIndex::build(&tasks));
+        let result = run_opened(&mut tasks, &mut network, None, Some("2024-01-08"), None);
+        
+        assert_eq!((tasks[0].start.as_str(), tasks[1].start.as_str()), ("2024-01-08", "2024-01-10"));
+        assert_eq!(result.stats.error, None);
+    }
+    
+    #[test]
+    fn nothing_to_start_from_leaves_tasks_unscheduled_and_not_critical() {
+        let (tasks, result) = calculated(vec![task("A", 2), after(task("B", 1), &["A"])], None);
+        
+        for task in &tasks {
+            assert_eq!(task.start, "");
+            assert_eq!(task.end, "");
+            assert_eq!(task.total_float_days, None);
+            assert_eq!(task.is_critical, Some(false));
+        }
+        assert!(result.stats.error.is_some());
+    }
+    
+    /// A 2-day task with a constraint
+    fn constrained(id: &str, constraint_type: ConstraintType, date: &str) -> Task {
+        Task {
+            constraint_type,
+            constraint_date: Some(date.to_string()),
+            ..task(id, 2)
+        }
+    }
+    
+    /// Early and late start and finish of a task
+    fn dates_of(task: &Task) -> [&str; 4] {
+        [&task.start, &task.end, task.late_start.as_deref().unwrap_or(""), task.late_finish.as_deref().unwrap_or("")]
+    }
+    
+    #[test]
+    fn each_constraint_limits_the_early_or_late_dates() {
+        // The 10-day task sets the project finish, Friday 2024-01-19
+        let cases = [
+            (ConstraintType::Asap, "2024-01-10", ["2024-01-08", "2024-01-09", "2024-01-18", "2024-01-19"]),
+            (ConstraintType::Snet, "2024-01-10", ["2024-01-10", "2024-01-11", "2024-01-18", "2024-01-19"]),
+            (ConstraintType::Fnet, "2024-01-12", ["2024-01-11", "2024-01-12", "2024-01-18", "2024-01-19"]),
+            (ConstraintType::Snlt, "2024-01-10", ["2024-01-08", "2024-01-09", "2024-01-10", "2024-01-11"]),
+            (ConstraintType::Fnlt, "2024-01-12", ["2024-01-08", "2024-01-09", "2024-01-11", "2024-01-12"]),
+            (ConstraintType::Mso, "2024-01-15", ["2024-01-15", "2024-01-16", "2024-01-15", "2024-01-16"]),
+            (ConstraintType::Mfo, "2024-01-12", ["2024-01-11", "2024-01-12", "2024-01-11", "2024-01-12"]),
+            (ConstraintType::Alap, "2024-01-10", ["2024-01-18", "2024-01-19", "2024-01-18", "2024-01-19"]),
+        ];
+        
+        for (constraint_type, date, expected) in cases {
+            let (tasks, _) = calculated(vec![task("A", 10), constrained("X", constraint_type, date)], Some("2024-01-08"));
+            assert_eq!(dates_of(&tasks[1]), expected, "{:?} {}", constraint_type, date);
+        }
+    }
+    
+    #[test]
+    fn mandatory_dates_override_logic_and_the_data_date() {
+        let early = constrained("B", ConstraintType::Mso, "2024-01-09");
+        let before_data_date = constrained("C", ConstraintType::Mfo, "2024-01-05");
+        let (tasks, _) = calculated(vec![task("A", 5), after(early, &["A"]), before_data_date], Some("2024-01-08"));
+        
+        assert_eq!((tasks[1].start.as_str(), tasks[1].end.as_str()), ("2024-01-09", "2024-01-10"));
+        assert_eq!((tasks[2].start.as_str(), tasks[2].end.as_str()), ("2024-01-04", "2024-01-05"));
+        assert_eq!(tasks[1].driven_by, Some(StartDriver::Constraint));
+    }
+    
+    #[test]
+    fn late_constraints_pull_in_the_late_dates_of_predecessors() {
+        let limited = constrained("B", ConstraintType::Fnlt, "2024-01-12");
+        let (tasks, _) = calculated(vec![task("A", 2), after(limited, &["A"]), task("C", 10)], Some("2024-01-08"));
+        
+        assert_eq!(dates_of(&tasks[0]), ["2024-01-08", "2024-01-09", "2024-01-09", "2024-01-10"]);
+        assert_eq!(tasks[0].total_float_days, Some(1));
+        assert_eq!(tasks[1].total_float_days, Some(1));
+    }
+    
+    #[test]
+    fn an_alap_task_finishes_just_before_its_successor_starts() {
+        let alap = Task { constraint_type: ConstraintType::Alap, ..task("X", 2) };
+        let tasks = vec![task("A", 5), alap, after(task("B", 2), &["A", "X"]), task("C", 10)];
+        let (tasks, _) = calculated(tasks, Some("2024-01-08"));
+        
+        // B starts Monday 2024-01-15 after A, so X takes the Thursday and Friday before
+        assert_eq!(dates_of(&tasks[1]), ["2024-01-11", "2024-01-12", "2024-01-16", "2024-01-17"]);
+        assert_eq!(tasks[2].start, "2024-01-15");
+    }
+    
+    fn due(mut task: Task, deadline: &str) -> Task {
+        task.deadline = Some(deadline.to_string());
+        task
+    }
+    
+    #[test]
+    fn a_deadline_limits_the_late_dates_without_moving_the_task() {
+        // A and B run from Monday 2024-01-08 to Wednesday 2024-01-10; C sets the finish
+        let (tasks, _) = calculated(vec![task("A", 1), after(due(task("B", 2), "2024-01-11"), &["A"]), task("C", 10)], Some("2024-01-08"));
+        
+        assert_eq!(dates_of(&tasks[1]), ["2024-01-09", "2024-01-10", "2024-01-10", "2024-01-11"]);
+        assert_eq!(tasks[1].total_float_days, Some(1));
+        // The predecessor loses the same float
+        assert_eq!(tasks[0].late_finish.as_deref(), Some("2024-01-09"));
+        assert_eq!(tasks[0].total_float_days, Some(1));
+    }
+    
+    #[test]
+    fn a_summary_deadline_limits_every_child() {
+        let summary = due(task("P", 0), "2024-01-10");
+        let (tasks, _) = calculated(vec![summary, child("A", "P", 1), child("B", "P", 2), task("C", 10)], Some("2024-01-08"));
+        
+        assert_eq!(tasks[1].late_finish.as_deref(), Some("2024-01-10"));
+        assert_eq!(tasks[2].late_finish.as_deref(), Some("2024-01-10"));
+        assert_eq!(tasks[2].total_float_days, Some(1));
+    }
+    
+    #[test]
+    fn deadline_variance_counts_the_work_days_to_spare() {
+        let tasks = vec![due(task("A", 3), "2024-01-12"), due(task("B", 5), "2024-01-10"), due(task("C", 3), "2024-01-10"), task("D", 2)];
+        let (tasks, result) = calculated(tasks, Some("2024-01-08"));
+        
+        let variances: Vec<Option<i32>> = tasks.iter().map(|t| t.deadline_variance).collect();
+        assert_eq!(variances, [Some(2), Some(-2), Some(0), None]);
+        assert_eq!(result.stats.missed_deadline_count, 1);
+        // Missing the deadline shows up as negative float, not a later bar
+        assert_eq!(tasks[1].end, "2024-01-12");
+        assert_eq!(tasks[1].total_float_days, Some(-2));
+    }
+    
+    #[test]
+    fn a_link_into_a_summary_holds_back_every_task_below_it() {
+        let tasks = vec![
+            task("A", 2),
+            after(task("P", 0), &["A"]),
+            child("B", "P", 1),
+            child("Q", "P", 0),
+            child("C", "Q", 2),
+        ];
+        let (tasks, _) = calculated(tasks, Some("2024-01-08"));
+        
+        // A finishes Tuesday 2024-01-09
+        for i in [1, 2, 3, 4] {
+            assert_eq!(tasks[i].start, "2024-01-10", "{}", tasks[i].id);
+        }
+        assert_eq!(tasks[2].driven_by, Some(StartDriver::Summary));
+        assert_eq!(tasks[1].driven_by, Some(StartDriver::Rollup));
+    }
+    
+    #[test]
+    fn a_summary_floor_gives_way_to_later_logic_below_it() {
+        let summary = Task {
+            constraint_type: ConstraintType::Snet,
+            constraint_date: Some("2024-01-09".to_string()),
+            ..task("P", 0)
+        };
+        let tasks = vec![task("A", 3), summary, child("B", "P", 1), after(child("C", "P", 1), &["A"])];
+        let (tasks, _) = calculated(tasks, Some("2024-01-08"));
+        
+        assert_eq!(tasks[2].start, "2024-01-09");
+        assert_eq!(tasks[3].start, "2024-01-11");
+        assert_eq!((tasks[1].start.as_str(), tasks[1].end.as_str()), ("2024-01-09", "2024-01-11"));
+    }
+    
+    #[test]
+    fn a_link_out_of_a_summary_waits_for_its_last_child_and_limits_them_all() {
+        let tasks = vec![
+            task("P", 0),
+            child("A", "P", 1),
+            child("B", "P", 3),
+            after(task("X", 2), &["P"]),
+        ];
+        let (tasks, _) = calculated(tasks, Some("2024-01-08"));
+        
+        // B finishes Wednesday 2024-01-10
+        assert_eq!(tasks[3].start, "2024-01-11");
+        assert_eq!(tasks[1].late_finish.as_deref(), Some("2024-01-10"));
+        assert_eq!(tasks[1].total_float_days, Some(2));
+        assert_eq!(tasks[2].total_float_days, Some(0));
+    }
+    
+    #[test]
+    fn finish_links_into_a_summary_do_not_schedule_it() {
+        let mut summary = task("P", 0);
+        summary.dependencies.push(Dependency { id: "A".to_string(), link_type: LinkType::Ff, lag: 0 });
+        let (tasks, _) = calculated(vec![task("A", 5), summary, child("B", "P", 1)], Some("2024-01-08"));
+        
+        assert_eq!((tasks[2].start.as_str(), tasks[2].end.as_str()), ("2024-01-08", "2024-01-08"));
+        assert_eq!(tasks[1].predecessor_links.as_deref().map(<[LinkAnalysis]>::len), Some(0));
+    }
+    
+    /// Predecessor, relative float and whether it drives, for each link of a task
+    fn links_of(task: &Task) -> Vec<(&str, i32, bool)> {
+        task.predecessor_links.iter().flatten()
+            .map(|l| (l.predecessor_id.as_str(), l.relative_float, l.driving))
+            .collect()
+    }
+    
+    #[test]
+    fn the_latest_predecessor_drives_and_the_others_have_relative_float() {
+        let mut c = after(task("C", 1), &["A", "B"]);
+        c.dependencies.push(Dependency { id: "S".to_string(), link_type: LinkType::Ss, lag: 1 });
+        let (tasks, _) = calculated(vec![task("A", 3), task("B", 1), task("S", 1), c], Some("2024-01-08"));
+        
+        assert_eq!(links_of(&tasks[3]), [("A", 0, true), ("B", 2, false), ("S", 2, false)]);
+        assert_eq!(tasks[3].driven_by, Some(StartDriver::Predecessor));
+    }
+    
+    #[test]
+    fn a_start_without_a_driving_link_names_what_set_it() {
+        let done = Task { actual_finish: Some("2024-01-05".to_string()), ..started("D", "2024-01-03") };
+        let tasks = vec![task("A", 1), snet("B", "2024-01-10"), after(snet("C", "2024-01-08"), &["A"]), done];
+        let (tasks, _) = calculated(tasks, Some("2024-01-08"));
+        
+        let drivers: Vec<Option<StartDriver>> = tasks.iter().map(|t| t.driven_by).collect();
+        assert_eq!(drivers, [Some(StartDriver::DataDate), Some(StartDriver::Constraint), Some(StartDriver::Predecessor), Some(StartDriver::Actuals)]);
+    }
+    
+    #[test]
+    fn the_driving_path_walks_back_through_driving_links_and_summaries() {
+        let tasks = vec![
+            task("X", 4),
+            after(task("A", 1), &["X"]),
+            task("B", 1),
+            after(task("P", 0), &["A", "B"]),
+            child("Y", "P", 1),
+        ];
+        let (tasks, _) = calculated(tasks, Some("2024-01-08"));
+        
+        let path: Vec<(String, Option<String>, Option<LinkType>)> = driving_path(&tasks, &TaskIndex::build(&tasks), "Y").into_iter()
+            .map(|step| (step.task_id, step.successor_id, step.link_type))
+            .collect();
+        let step = |id: &str, successor: Option<&str>, link_type| (id.to_string(), successor.map(str::to_string), link_type);
+        assert_eq!(path, [
+            step("Y", None, None),
+            step("P", Some("Y"), None),
+            step("A", Some("P"), Some(LinkType::Fs)),
+            step("X", Some("A"), Some(LinkType::Fs)),
+        ]);
+        assert!(driving_path(&tasks, &TaskIndex::build(&tasks), "Missing").is_empty());
+    }
+    
+    fn calculated_with(mut tasks: Vec<Task>, options: &ScheduleOptions) -> (Vec<Task>, CPMResult) {
+        let calendar = weekdays();
+        let named = HashMap::new();
+        let calendars = Calendars::new(&calendar, &named, None, options);
+        let index = TaskIndex::build(&tasks);
+        let mut network = Network::build(&tasks, &index);
+        let result = calculate(&mut tasks, &calendars, options, Some("2024-01-08"), None, &mut network, &index, None);
+        (tasks, result)
+    }
+    
+    /// A and E-F finish Friday 2024-01-19; B misses its deadline by a day,
+    /// C has 3 days of float and D 8
+    fn float_spread() -> Vec<Task> {
+        vec![
+            task("A", 10),
+            due(task("B", 2), "2024-01-08"),
+            task("C", 7),
+            task("D", 2),
+            task("E", 4),
+            after(task("F", 6), &["E"]),
+        ]
+    }
+    
+    /// IDs of the critical and near-critical tasks
+    fn marked(tasks: &[Task]) -> (Vec<&str>, Vec<&str>) {
+        let ids = |flag: fn(&Task) -> Option<bool>| tasks.iter().filter(|&t| flag(t) == Some(true)).map(|t| t.id.as_str()).collect();
+        (ids(|t| t.is_critical), ids(|t| t.is_near_critical))
+    }
+    
+    #[test]
+    fn total_float_mode_marks_float_at_or_below_the_threshold() {
+        let (tasks, result) = calculated_with(float_spread(), &ScheduleOptions::default());
+        assert_eq!(marked(&tasks), (vec!["A", "B", "E", "F"], vec!["C"]));
+        assert_eq!((result.stats.critical_count, result.stats.near_critical_count, result.stats.non_critical_count), (4, 1, 1));
+        
+        let options = ScheduleOptions { critical_float_threshold: 3, near_critical_threshold: 8, ..ScheduleOptions::default() };
+        let (tasks, _) = calculated_with(float_spread(), &options);
+        assert_eq!(marked(&tasks), (vec!["A", "B", "C", "E", "F"], vec!["D"]));
+    }
+    
+    #[test]
+    fn longest_path_mode_marks_the_chains_driving_the_finish() {
+        let options = ScheduleOptions { critical_path: CriticalPathMode::LongestPath, ..ScheduleOptions::default() };
+        let (tasks, _) = calculated_with(float_spread(), &options);
+        
+        // Negative float alone does not put B on the longest path
+        assert_eq!(marked(&tasks), (vec!["A", "E", "F"], vec!["B", "C"]));
+    }
+    
+    #[test]
+    fn a_summary_is_marked_like_its_most_critical_child() {
+        let tasks = vec![task("A", 10), task("P", 0), child("B", "P", 7), child("C", "P", 2), task("Q", 0), child("D", "Q", 1)];
+        let (tasks, _) = calculated_with(tasks, &ScheduleOptions { near_critical_threshold: 3, ..ScheduleOptions::default() });
+        
+        assert_eq!(marked(&tasks), (vec!["A"], vec!["P", "B"]));
     }
 }
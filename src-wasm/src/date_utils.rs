@@ -1,33 +1,58 @@
 //! Date utility functions for working day calculations
 //! 
 //! Ported from src/core/DateUtils.ts
-//! Handles working day calculations with calendar awareness (holidays, weekends)
+//! Handles working day calculations with calendar awareness (holidays, weekends),
+//! and working-minute calculations on the calendar's work hours
 
-use crate::types::Calendar;
-use chrono::{NaiveDate, Datelike, Weekday};
+use crate::types::{Calendar, CalendarException, CalendarRule, ExceptionDay, Recurrence, WorkPeriod};
+use chrono::{NaiveDate, Datelike, Days, Weekday};
+use std::fmt;
+
+/// Longest run of calendar days a search crosses without finding working
+/// time before giving up
+pub const SEARCH_HORIZON_DAYS: u32 = 4 * 366;
+
+/// Longest exception span (range exception, range or weekly rule) a calendar
+/// accepts - keeps every gap in working time well inside the search horizon
+pub const MAX_EXCEPTION_SPAN_DAYS: i64 = 3 * 366;
+
+/// A date calculation that could not finish
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DateError {
+    /// No working time within `SEARCH_HORIZON_DAYS` of `from` (or the end
+    /// of the supported date range was reached first)
+    NoWorkingTime { from: String },
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateError::NoWorkingTime { from } => write!(
+                f,
+                "no working time within {} days of {} - check the calendar",
+                SEARCH_HORIZON_DAYS, from
+            ),
+        }
+    }
+}
 
 /// Check if a date is a working day based on the calendar
 pub fn is_work_day(date: &NaiveDate, calendar: &Calendar) -> bool {
-    // Check exceptions first
-    let date_str = date.format("%Y-%m-%d").to_string();
-    
-    // Check if there's an exception for this date
-    if let Some(exception) = calendar.exceptions.get(&date_str) {
-        // Handle both string and object formats
-        if let Some(obj) = exception.as_object() {
-            if let Some(working) = obj.get("working") {
-                if let Some(working_bool) = working.as_bool() {
-                    return working_bool;
-                }
-            }
-        }
-        // String exceptions are non-working days
-        return false;
+    // Check exceptions (date-specific, then recurring rules) first
+    match day_exception(date, calendar) {
+        Some(exception) => exception.working,
+        None => is_working_weekday(date, calendar),
     }
-    
-    // Check working days (0=Sunday, 1=Monday, etc.)
-    let day_of_week = date.weekday();
-    let day_index = match day_of_week {
+}
+
+/// Check the calendar's regular working weekdays, ignoring exceptions
+fn is_working_weekday(date: &NaiveDate, calendar: &Calendar) -> bool {
+    calendar.working_days.contains(&weekday_index(date))
+}
+
+/// Weekday number as used by calendars (0=Sunday, 1=Monday, etc.)
+fn weekday_index(date: &NaiveDate) -> i32 {
+    match date.weekday() {
         Weekday::Sun => 0,
         Weekday::Mon => 1,
         Weekday::Tue => 2,
@@ -35,56 +60,311 @@ pub fn is_work_day(date: &NaiveDate, calendar: &Calendar) -> bool {
         Weekday::Thu => 4,
         Weekday::Fri => 5,
         Weekday::Sat => 6,
+    }
+}
+
+/// The exception deciding a date's working status
+struct DayException<'a> {
+    working: bool,
+    description: Option<&'a str>,
+    /// Working hours of the day, if the exception sets them
+    hours: Option<&'a [WorkPeriod]>,
+    /// Comes from a weekly override rather than a specific date
+    weekly: bool,
+}
+
+/// Find the exception that applies to a date, if any
+///
+/// Date-specific exceptions win over recurring rules, and rules naming
+/// particular days (holidays, shutdowns) over weekly overrides. Among rules
+/// of the same kind the first match wins.
+fn day_exception<'a>(date: &NaiveDate, calendar: &'a Calendar) -> Option<DayException<'a>> {
+    // Check if there's an exception for this date, or a range covering it
+    // (keys are first dates, so a covering range starts at or before it -
+    // and no longer ago than the longest range a calendar accepts)
+    let earliest = date.checked_sub_days(Days::new(MAX_EXCEPTION_SPAN_DAYS as u64)).unwrap_or(NaiveDate::MIN);
+    let covering = calendar.exceptions.range(earliest..=*date).rev()
+        .map(|(_, exception)| exception)
+        .find(|e| e.date == *date || e.end.is_some_and(|end| end >= *date));
+    if let Some(exception) = covering {
+        return Some(DayException {
+            working: exception.working,
+            description: exception.description.as_deref(),
+            hours: exception.hours.as_deref(),
+            weekly: false,
+        });
+    }
+    
+    let is_weekly = |rule: &&CalendarRule| matches!(rule.repeat, Recurrence::Weekly { .. });
+    let rules = calendar.rules.iter().filter(|r| !is_weekly(r))
+        .chain(calendar.rules.iter().filter(is_weekly));
+    for rule in rules {
+        if let Some(working) = rule_status(rule, date) {
+            return Some(DayException {
+                working,
+                description: rule.description.as_deref(),
+                hours: None,
+                weekly: is_weekly(&rule),
+            });
+        }
+    }
+    
+    None
+}
+
+/// Parse a "YYYY-MM-DD" calendar date
+pub fn parse_day(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}' (expected YYYY-MM-DD)", date))
+}
+
+/// Check a date-specific exception is well formed
+///
+/// A range must not end before it starts, and working hours must be "HH:MM" periods on a working exception.
+pub fn validate_exception(exception: &CalendarException) -> Result<(), String> {
+    if let Some(end) = exception.end {
+        validate_span(exception.date, end)?;
+    }
+    
+    if let Some(hours) = &exception.hours {
+        if !exception.working {
+            return Err("working hours given for a non-working exception".to_string());
+        }
+        validate_periods(hours)?;
+    }
+    
+    Ok(())
+}
+
+/// Check a whole calendar can be scheduled on
+///
+/// At least one weekday must work, weekdays and work hours must be well
+/// formed, and recurring rules must name real days with bounded spans.
+/// Date-specific exceptions are checked as they are read.
+pub fn validate_calendar(calendar: &Calendar) -> Result<(), String> {
+    validate_weekdays(&calendar.working_days)?;
+    if calendar.working_days.is_empty() {
+        return Err("no working days in the week".to_string());
+    }
+    
+    for (weekday, periods) in &calendar.work_hours {
+        let day: i32 = weekday.parse()
+            .ok()
+            .filter(|d| (0..7).contains(d))
+            .ok_or_else(|| format!("invalid work hours weekday '{}' (expected 0-6)", weekday))?;
+        validate_periods(periods).map_err(|e| format!("work hours for weekday {}: {}", day, e))?;
+        if periods.is_empty() && calendar.working_days.contains(&day) {
+            return Err(format!("working weekday {} has no work hours", day));
+        }
+    }
+    
+    for rule in &calendar.rules {
+        validate_rule(rule).map_err(|e| match &rule.description {
+            Some(description) => format!("rule '{}': {}", description, e),
+            None => format!("rule: {}", e),
+        })?;
+    }
+    
+    Ok(())
+}
+
+/// Check a recurring rule names real days and a bounded span
+fn validate_rule(rule: &CalendarRule) -> Result<(), String> {
+    match &rule.repeat {
+        // Checked against a leap year so February 29 is allowed
+        Recurrence::Annual { month, day } => {
+            if NaiveDate::from_ymd_opt(2000, *month, *day).is_none() {
+                return Err(format!("invalid annual date {}/{}", month, day));
+            }
+        }
+        Recurrence::NthWeekday { month, weekday, nth } => {
+            if !(1..=12).contains(month) {
+                return Err(format!("invalid month {}", month));
+            }
+            if *weekday > 6 {
+                return Err(format!("invalid weekday {} (expected 0-6)", weekday));
+            }
+            if *nth == 0 || nth.abs() > 5 {
+                return Err(format!("invalid occurrence {} (expected 1 to 5, or -1 to -5)", nth));
+            }
+        }
+        Recurrence::Range { start, end } => validate_span(*start, *end)?,
+        Recurrence::Weekly { start, end, working_days } => {
+            validate_span(*start, *end)?;
+            validate_weekdays(working_days)?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Check an inclusive date span runs forward and stays within
+/// `MAX_EXCEPTION_SPAN_DAYS`
+fn validate_span(start: NaiveDate, end: NaiveDate) -> Result<(), String> {
+    if end < start {
+        return Err(format!("range end '{}' is before its start '{}'", end, start));
+    }
+    let days = (end - start).num_days() + 1;
+    if days > MAX_EXCEPTION_SPAN_DAYS {
+        return Err(format!(
+            "range {} to {} spans {} days (at most {} allowed)",
+            start, end, days, MAX_EXCEPTION_SPAN_DAYS
+        ));
+    }
+    Ok(())
+}
+
+/// Check weekday numbers are 0 (Sunday) to 6 (Saturday)
+fn validate_weekdays(days: &[i32]) -> Result<(), String> {
+    match days.iter().find(|d| !(0..7).contains(*d)) {
+        Some(day) => Err(format!("invalid weekday {} (expected 0-6)", day)),
+        None => Ok(()),
+    }
+}
+
+/// Check work periods are "HH:MM" times that end after they start
+fn validate_periods(periods: &[WorkPeriod]) -> Result<(), String> {
+    for period in periods {
+        match (parse_clock(&period.start), parse_clock(&period.end)) {
+            (Some(start), Some(end)) if start < end => {}
+            (Some(_), Some(_)) => {
+                return Err(format!("work period {}-{} ends before it starts", period.start, period.end));
+            }
+            _ => return Err(format!("invalid work period {}-{} (expected HH:MM)", period.start, period.end)),
+        }
+    }
+    Ok(())
+}
+
+/// Working status a rule gives a date, or None if it doesn't apply
+fn rule_status(rule: &CalendarRule, date: &NaiveDate) -> Option<bool> {
+    let applies = match &rule.repeat {
+        Recurrence::Annual { month, day } => date.month() == *month && date.day() == *day,
+        Recurrence::NthWeekday { month, weekday, nth } => {
+            date.month() == *month
+                && weekday_index(date) == *weekday as i32
+                && nth_in_month(date, *nth < 0) == nth.abs()
+        }
+        Recurrence::Range { start, end } => start <= date && date <= end,
+        Recurrence::Weekly { start, end, working_days } => {
+            let in_period = start <= date && date <= end;
+            return in_period.then(|| working_days.contains(&weekday_index(date)));
+        }
     };
     
-    calendar.working_days.contains(&day_index)
+    applies.then_some(rule.working)
+}
+
+/// Which occurrence of its weekday a date is within its month
+/// (1 = first, or 1 = last when counting from the end)
+fn nth_in_month(date: &NaiveDate, from_end: bool) -> i32 {
+    let day = date.day() as i32;
+    if !from_end {
+        return (day - 1) / 7 + 1;
+    }
+    
+    let next_month = if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+    };
+    let days_in_month = next_month.and_then(|d| d.pred_opt()).map_or(31, |d| d.day() as i32);
+    (days_in_month - day) / 7 + 1
+}
+
+/// List the exception days between two dates (inclusive), for display
+///
+/// Includes every date a date-specific exception or a date rule applies to,
+/// and the dates where a weekly override changes the usual working weekdays.
+pub fn expand_exceptions(calendar: &Calendar, start: NaiveDate, end: NaiveDate) -> Vec<ExceptionDay> {
+    let mut days = Vec::new();
+    let mut date = start;
+    
+    while date <= end {
+        if let Some(exception) = day_exception(&date, calendar) {
+            if !exception.weekly || exception.working != is_working_weekday(&date, calendar) {
+                days.push(ExceptionDay {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    working: exception.working,
+                    description: exception.description.map(str::to_string),
+                });
+            }
+        }
+        date = match date.succ_opt() {
+            Some(d) => d,
+            None => break,
+        };
+    }
+    
+    days
 }
 
 /// Add working days to a date string
 /// Returns result date string in "YYYY-MM-DD" format
-pub fn add_work_days(date_str: &str, days: i32, calendar: &Calendar) -> String {
+pub fn add_work_days(date_str: &str, days: i32, calendar: &Calendar) -> Result<String, DateError> {
     if date_str.is_empty() {
-        return date_str.to_string();
+        return Ok(date_str.to_string());
     }
     
-    let mut date = match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+    let date = match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
         Ok(d) => d,
-        Err(_) => return date_str.to_string(),
+        Err(_) => return Ok(date_str.to_string()),
     };
     
     // Special case: when days is 0, adjust to next working day if current date is non-working
     if days == 0 {
-        while !is_work_day(&date, calendar) {
-            date = date.succ_opt().unwrap_or(date);
-        }
-        return date.format("%Y-%m-%d").to_string();
+        return work_day_from(date, true, calendar).map(|d| d.format("%Y-%m-%d").to_string());
     }
     
-    let direction = if days >= 0 { 1 } else { -1 };
-    let mut remaining = days.abs();
-    
     // Move through calendar days, counting only working days
-    while remaining > 0 {
-        if direction > 0 {
-            date = date.succ_opt().unwrap_or(date);
-        } else {
-            date = date.pred_opt().unwrap_or(date);
-        }
-        if is_work_day(&date, calendar) {
-            remaining -= 1;
-        }
+    let forward = days > 0;
+    let mut date = date;
+    for _ in 0..days.unsigned_abs() {
+        let next = step_day(date, forward).ok_or_else(|| no_working_time(date))?;
+        date = work_day_from(next, forward, calendar)?;
     }
     
-    // Ensure we land on a working day (edge case handling)
-    while !is_work_day(&date, calendar) {
-        if direction > 0 {
-            date = date.succ_opt().unwrap_or(date);
-        } else {
-            date = date.pred_opt().unwrap_or(date);
+    Ok(date.format("%Y-%m-%d").to_string())
+}
+
+/// Latest working day on or before a date
+/// Returns result date string in "YYYY-MM-DD" format
+pub fn work_day_on_or_before(date_str: &str, calendar: &Calendar) -> Result<String, DateError> {
+    let date = match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+        Ok(d) => d,
+        Err(_) => return Ok(date_str.to_string()),
+    };
+    
+    work_day_from(date, false, calendar).map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/// First working day on or after `date` (on or before when moving backward),
+/// searching at most `SEARCH_HORIZON_DAYS`
+fn work_day_from(date: NaiveDate, forward: bool, calendar: &Calendar) -> Result<NaiveDate, DateError> {
+    let mut current = date;
+    for _ in 0..=SEARCH_HORIZON_DAYS {
+        if is_work_day(&current, calendar) {
+            return Ok(current);
         }
+        current = match step_day(current, forward) {
+            Some(d) => d,
+            None => break,
+        };
     }
-    
-    date.format("%Y-%m-%d").to_string()
+    Err(no_working_time(date))
+}
+
+/// The next (or previous) calendar day, None past the supported range
+fn step_day(date: NaiveDate, forward: bool) -> Option<NaiveDate> {
+    if forward {
+        date.succ_opt()
+    } else {
+        date.pred_opt()
+    }
+}
+
+fn no_working_time(from: NaiveDate) -> DateError {
+    DateError::NoWorkingTime { from: from.format("%Y-%m-%d").to_string() }
 }
 
 /// Calculate working days between two dates (inclusive)
@@ -176,7 +456,277 @@ pub fn calc_work_days_difference(start_str: &str, end_str: &str, calendar: &Cale
     count
 }
 
-/// Get today's date as an ISO string
-pub fn today() -> String {
-    chrono::Utc::now().format("%Y-%m-%d").to_string()
+// === Working hours ===
+//
+// When scheduling in hours, dates are moments "YYYY-MM-DDTHH:MM". A start is
+// the moment work begins and a finish the moment it ends, so a finish can
+// sit at the end of a work period (e.g. 11:30 before lunch).
+
+/// Minutes in a day
+const DAY_MINUTES: u32 = 24 * 60;
+
+/// Work periods of a working day that has no hours of its own
+const DEFAULT_WORK_PERIODS: [(u32, u32); 2] = [(8 * 60, 12 * 60), (13 * 60, 17 * 60)];
+
+/// Parse "HH:MM" (seconds are ignored) into minutes from midnight, up to "24:00"
+fn parse_clock(time: &str) -> Option<u32> {
+    let mut parts = time.split(':');
+    let hours: u32 = parts.next()?.parse().ok()?;
+    let minutes: u32 = parts.next()?.parse().ok()?;
+    let total = hours * 60 + minutes;
+    (minutes < 60 && total <= DAY_MINUTES).then_some(total)
+}
+
+/// Parse a moment "YYYY-MM-DDTHH:MM" into its date and minute of the day
+///
+/// A plain date is taken as midnight.
+pub fn parse_moment(moment: &str) -> Option<(NaiveDate, u32)> {
+    let (date, time) = moment.split_once('T').unwrap_or((moment, "00:00"));
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some((date, parse_clock(time)?))
+}
+
+/// Format a moment as "YYYY-MM-DDTHH:MM" (midnight at the end of a day is
+/// written as 00:00 the next day)
+pub fn format_moment(date: NaiveDate, minute: u32) -> String {
+    let (date, minute) = if minute >= DAY_MINUTES {
+        (date.succ_opt().unwrap_or(date), minute - DAY_MINUTES)
+    } else {
+        (date, minute)
+    };
+    format!("{}T{:02}:{:02}", date.format("%Y-%m-%d"), minute / 60, minute % 60)
+}
+
+/// Working periods of a date in minutes from midnight, in order
+/// (empty on non-working days)
+pub fn work_periods(date: &NaiveDate, calendar: &Calendar) -> Vec<(u32, u32)> {
+    let exception = day_exception(date, calendar);
+    let working = match &exception {
+        Some(exception) => exception.working,
+        None => is_working_weekday(date, calendar),
+    };
+    if !working {
+        return Vec::new();
+    }
+    
+    let weekday = date.weekday().num_days_from_sunday().to_string();
+    let Some(periods) = exception.and_then(|e| e.hours)
+        .or_else(|| calendar.work_hours.get(&weekday).map(Vec::as_slice))
+    else {
+        return DEFAULT_WORK_PERIODS.to_vec();
+    };
+    
+    let mut periods: Vec<(u32, u32)> = periods.iter()
+        .filter_map(|p| Some((parse_clock(&p.start)?, parse_clock(&p.end)?)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    periods.sort_unstable();
+    periods
+}
+
+/// Add working minutes to a moment
+///
+/// Moving forward, zero minutes snaps to the next moment work can start;
+/// moving backward (negative minutes) the result can be the end of a work
+/// period. Returns result moment in "YYYY-MM-DDTHH:MM" format.
+pub fn add_work_minutes(moment_str: &str, minutes: i64, calendar: &Calendar) -> Result<String, DateError> {
+    let Some((date, minute)) = parse_moment(moment_str) else {
+        return Ok(moment_str.to_string());
+    };
+    
+    if minutes >= 0 {
+        walk_forward(date, minute, minutes as u64, calendar)
+    } else {
+        walk_backward(date, minute, minutes.unsigned_abs(), calendar)
+    }
+}
+
+/// Latest moment on or before `moment_str` that lies in (or at the end of)
+/// working time - where work finishing by then can finish
+pub fn work_time_on_or_before(moment_str: &str, calendar: &Calendar) -> Result<String, DateError> {
+    match parse_moment(moment_str) {
+        Some((date, minute)) => walk_backward(date, minute, 0, calendar),
+        None => Ok(moment_str.to_string()),
+    }
+}
+
+/// Count working minutes forward from a moment, giving up after
+/// `SEARCH_HORIZON_DAYS` days in a row without working time
+fn walk_forward(mut date: NaiveDate, mut minute: u32, mut remaining: u64, calendar: &Calendar) -> Result<String, DateError> {
+    let origin = date;
+    let mut idle_days = 0;
+    loop {
+        let mut worked = false;
+        for (start, end) in work_periods(&date, calendar) {
+            if end <= minute {
+                continue;
+            }
+            let from = start.max(minute);
+            let available = (end - from) as u64;
+            if remaining <= available {
+                return Ok(format_moment(date, from + remaining as u32));
+            }
+            remaining -= available;
+            worked = true;
+        }
+        idle_days = if worked { 0 } else { idle_days + 1 };
+        date = match date.succ_opt() {
+            Some(d) if idle_days <= SEARCH_HORIZON_DAYS => d,
+            _ => return Err(no_working_time(origin)),
+        };
+        minute = 0;
+    }
+}
+
+/// Count working minutes backward from a moment, giving up after
+/// `SEARCH_HORIZON_DAYS` days in a row without working time
+fn walk_backward(mut date: NaiveDate, mut minute: u32, mut remaining: u64, calendar: &Calendar) -> Result<String, DateError> {
+    let origin = date;
+    let mut idle_days = 0;
+    loop {
+        let mut worked = false;
+        for (start, end) in work_periods(&date, calendar).into_iter().rev() {
+            if start >= minute {
+                continue;
+            }
+            let to = end.min(minute);
+            let available = (to - start) as u64;
+            if remaining <= available {
+                return Ok(format_moment(date, to - remaining as u32));
+            }
+            remaining -= available;
+            worked = true;
+        }
+        idle_days = if worked { 0 } else { idle_days + 1 };
+        date = match date.pred_opt() {
+            Some(d) if idle_days <= SEARCH_HORIZON_DAYS => d,
+            _ => return Err(no_working_time(origin)),
+        };
+        minute = DAY_MINUTES;
+    }
+}
+
+/// Working minutes from one moment to another
+/// Returns signed minutes (positive if end_str is later than start_str)
+pub fn work_minutes_between(start_str: &str, end_str: &str, calendar: &Calendar) -> i64 {
+    let (Some(start), Some(end)) = (parse_moment(start_str), parse_moment(end_str)) else {
+        return 0;
+    };
+    
+    let (from, to, sign) = if start <= end { (start, end, 1) } else { (end, start, -1) };
+    let mut total: i64 = 0;
+    let mut date = from.0;
+    while date <= to.0 {
+        let lo = if date == from.0 { from.1 } else { 0 };
+        let hi = if date == to.0 { to.1 } else { DAY_MINUTES };
+        for (start, end) in work_periods(&date, calendar) {
+            let (a, b) = (start.max(lo), end.min(hi));
+            if a < b {
+                total += (b - a) as i64;
+            }
+        }
+        date = match date.succ_opt() {
+            Some(d) => d,
+            None => break,
+        };
+    }
+    
+    sign * total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    /// Monday-to-Friday calendar with the given exceptions
+    fn calendar(exceptions: serde_json::Value) -> Calendar {
+        serde_json::from_value(json!({ "workingDays": [1, 2, 3, 4, 5], "exceptions": exceptions })).expect("valid calendar")
+    }
+    
+    fn day(date: &str) -> NaiveDate {
+        parse_day(date).expect("valid date")
+    }
+    
+    #[test]
+    fn a_range_exception_covers_every_day_up_to_its_end() {
+        let calendar = calendar(json!({
+            "2024-01-08": { "end": "2024-01-10", "description": "Shutdown" },
+            "2024-01-09": "Holiday",
+        }));
+        let working: Vec<bool> = ["2024-01-05", "2024-01-08", "2024-01-09", "2024-01-10", "2024-01-11"]
+            .iter()
+            .map(|d| is_work_day(&day(d), &calendar))
+            .collect();
+        assert_eq!(working, [true, false, false, false, true]);
+    }
+    
+    #[test]
+    fn the_longest_range_reaches_its_last_day() {
+        // Wednesday, and the first day of a range as long as allowed
+        let end = day("2026-01-07");
+        let start = end - Days::new(MAX_EXCEPTION_SPAN_DAYS as u64 - 1);
+        let calendar = calendar(json!({ start.to_string(): { "end": "2026-01-07" } }));
+        assert!(!is_work_day(&end, &calendar));
+        assert!(is_work_day(&day("2026-01-08"), &calendar));
+    }
+    
+    #[test]
+    fn exception_keys_must_be_dates_matching_the_entry() {
+        let read = |exceptions| serde_json::from_value::<Calendar>(json!({ "workingDays": [1], "exceptions": exceptions }));
+        let error = read(json!({ "Christmas": "Holiday" })).unwrap_err().to_string();
+        assert!(error.contains("invalid date 'Christmas'"), "{}", error);
+        let error = read(json!({ "2024-12-25": { "date": "2024-12-26" } })).unwrap_err().to_string();
+        assert!(error.contains("does not match its key"), "{}", error);
+    }
+    
+    /// Monday-to-Friday calendar with the given working hours by weekday
+    fn hours(work_hours: serde_json::Value, exceptions: serde_json::Value) -> Calendar {
+        serde_json::from_value(json!({ "workingDays": [1, 2, 3, 4, 5], "workHours": work_hours, "exceptions": exceptions }))
+            .expect("valid calendar")
+    }
+    
+    #[test]
+    fn work_minutes_skip_breaks_nights_and_weekends() {
+        let calendar = calendar(json!({}));
+        let add = |moment, minutes| add_work_minutes(moment, minutes, &calendar).unwrap();
+        
+        // Friday 16:00 plus two hours runs into Monday
+        assert_eq!(add("2024-01-12T16:00", 120), "2024-01-15T09:00");
+        assert_eq!(add("2024-01-15T09:00", -120), "2024-01-12T16:00");
+        // No minutes: the next moment work can start (after lunch here)
+        assert_eq!(add("2024-01-12T12:00", 0), "2024-01-12T13:00");
+        assert_eq!(work_time_on_or_before("2024-01-13T10:00", &calendar).unwrap(), "2024-01-12T17:00");
+        
+        assert_eq!(work_minutes_between("2024-01-12T16:00", "2024-01-15T09:00", &calendar), 120);
+        assert_eq!(work_minutes_between("2024-01-15T09:00", "2024-01-12T16:00", &calendar), -120);
+    }
+    
+    #[test]
+    fn each_weekday_can_have_its_own_hours() {
+        // Fridays are a short day, 07:00-11:00
+        let calendar = hours(json!({ "5": [{ "start": "07:00", "end": "11:00" }] }), json!({}));
+        
+        assert_eq!(add_work_minutes("2024-01-12T10:00", 120, &calendar).unwrap(), "2024-01-15T09:00");
+        assert_eq!(add_work_minutes("2024-01-15T08:00", -60, &calendar).unwrap(), "2024-01-12T10:00");
+        assert_eq!(work_minutes_between("2024-01-12T00:00", "2024-01-13T00:00", &calendar), 240);
+        assert_eq!(work_minutes_between("2024-01-11T00:00", "2024-01-12T00:00", &calendar), 480);
+    }
+    
+    #[test]
+    fn an_exception_can_add_a_day_with_its_own_hours() {
+        let calendar = hours(json!({}), json!({
+            "2024-01-13": { "working": true, "hours": [{ "start": "09:00", "end": "12:00" }] },
+        }));
+        
+        assert_eq!(add_work_minutes("2024-01-12T16:00", 120, &calendar).unwrap(), "2024-01-13T10:00");
+        assert_eq!(work_time_on_or_before("2024-01-14T10:00", &calendar).unwrap(), "2024-01-13T12:00");
+    }
+    
+    #[test]
+    fn a_walk_without_working_time_gives_up() {
+        let calendar: Calendar = serde_json::from_value(json!({ "workingDays": [] })).expect("valid calendar");
+        assert!(add_work_minutes("2024-01-08T08:00", 60, &calendar).is_err());
+        assert!(add_work_minutes("2024-01-08T08:00", -60, &calendar).is_err());
+    }
 }
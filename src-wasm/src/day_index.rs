@@ -0,0 +1,227 @@
+//! Precomputed working-day tables for constant-time date arithmetic
+//!
+//! Walking a calendar one day at a time for every `add_work_days` call is
+//! what large multi-year schedules spent most of their CPM time on. A
+//! `DayIndex` evaluates a calendar once over the project horizon and keeps:
+//! - `before`: for each day, how many working days precede it in the table
+//! - `work_days`: the working days themselves, in order
+//!
+//! Dates are day numbers (days from the Common Era) - the same ordinals the
+//! CPM passes carry (see `calendars::Moment`) - so adding N working days or
+//! counting the working days between two dates is a couple of lookups with
+//! no parsing or formatting. Dates outside the table return None and the
+//! caller falls back to walking the calendar (see `date_utils`).
+//!
+//! Tables are only used when scheduling in days; hours still walk each day's
+//! work periods.
+
+use crate::date_utils::is_work_day;
+use crate::types::{Calendar, Task};
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+
+/// Days covered before the earliest date found in the schedule
+const HORIZON_BEFORE_DAYS: i64 = 2 * 366;
+
+/// Days covered after the latest date found in the schedule (room for the
+/// schedule to grow before lookups fall back to walking)
+const HORIZON_AFTER_DAYS: i64 = 10 * 366;
+
+/// Working-day table of one calendar over a fixed range of days
+pub struct DayIndex {
+    /// Day number of the first day in the table
+    first: i32,
+    /// `before[d - first]` = working days in the table before day `d`
+    /// (one entry more than there are days)
+    before: Vec<u32>,
+    /// Day numbers of the working days, in order
+    work_days: Vec<i32>,
+}
+
+impl DayIndex {
+    /// Evaluate `calendar` for every day from `start` to `end` (inclusive)
+    pub fn build(calendar: &Calendar, start: NaiveDate, end: NaiveDate) -> DayIndex {
+        let mut before = vec![0];
+        let mut work_days = Vec::new();
+        let mut date = start;
+
+        while date <= end {
+            if is_work_day(&date, calendar) {
+                work_days.push(date.num_days_from_ce());
+            }
+            before.push(work_days.len() as u32);
+            date = match date.succ_opt() {
+                Some(d) => d,
+                None => break,
+            };
+        }
+
+        DayIndex { first: start.num_days_from_ce(), before, work_days }
+    }
+
+    /// Position of `day` in the table, if the table covers it
+    fn slot(&self, day: i32) -> Option<usize> {
+        let slot = usize::try_from(day.checked_sub(self.first)?).ok()?;
+        (slot + 1 < self.before.len()).then_some(slot)
+    }
+
+    /// The `k`-th working day of the table (0-based)
+    fn work_day(&self, k: i64) -> Option<i32> {
+        self.work_days.get(usize::try_from(k).ok()?).copied()
+    }
+
+    /// Same as `date_utils::add_work_days` on day numbers: zero days snaps
+    /// forward to a working day, otherwise counts `days` working days
+    /// forward or back
+    pub fn add_work_days(&self, day: i32, days: i32) -> Option<i32> {
+        let slot = self.slot(day)?;
+        let k = match days {
+            0 => self.before[slot] as i64,
+            d if d > 0 => self.before[slot + 1] as i64 + d as i64 - 1,
+            d => self.before[slot] as i64 + d as i64,
+        };
+        self.work_day(k)
+    }
+
+    /// Latest working day on or before `day`
+    pub fn work_day_on_or_before(&self, day: i32) -> Option<i32> {
+        let slot = self.slot(day)?;
+        self.work_day(self.before[slot + 1] as i64 - 1)
+    }
+
+    /// Same as `date_utils::calc_work_days_difference`: working days after
+    /// `start` up to and including `end`, negative if `end` is earlier
+    pub fn work_days_difference(&self, start: i32, end: i32) -> Option<i32> {
+        let start = self.slot(start)?;
+        let end = self.slot(end)?;
+        Some(self.before[end + 1] as i32 - self.before[start + 1] as i32)
+    }
+
+    /// Same as `date_utils::calc_work_days`: working days from `start` to
+    /// `end` inclusive (either order), at least 1
+    pub fn work_days_inclusive(&self, start: i32, end: i32) -> Option<i32> {
+        let (start, end) = (start.min(end), start.max(end));
+        let start = self.slot(start)?;
+        let end = self.slot(end)?;
+        Some((self.before[end + 1] - self.before[start]).max(1) as i32)
+    }
+}
+
+/// Working-day tables of the project calendar and every named calendar,
+/// covering the same horizon
+pub struct DayIndexes {
+    project: DayIndex,
+    named: HashMap<String, DayIndex>,
+}
+
+impl DayIndexes {
+    /// Build tables covering the dates found in `tasks` and the data date,
+    /// padded on both sides. None when the schedule has no dates yet.
+    pub fn build(project: &Calendar, named: &HashMap<String, Calendar>, tasks: &[Task], data_date: Option<&str>) -> Option<DayIndexes> {
+        let (earliest, latest) = schedule_span(tasks, data_date)?;
+        let start = earliest.checked_sub_signed(chrono::Duration::days(HORIZON_BEFORE_DAYS)).unwrap_or(earliest);
+        let end = latest.checked_add_signed(chrono::Duration::days(HORIZON_AFTER_DAYS)).unwrap_or(latest);
+
+        Some(DayIndexes {
+            project: DayIndex::build(project, start, end),
+            named: named.iter()
+                .map(|(id, calendar)| (id.clone(), DayIndex::build(calendar, start, end)))
+                .collect(),
+        })
+    }
+
+    /// Table of the project calendar
+    pub fn project(&self) -> &DayIndex {
+        &self.project
+    }
+
+    /// Table of a named calendar
+    pub fn named(&self, id: &str) -> Option<&DayIndex> {
+        self.named.get(id)
+    }
+}
+
+/// Earliest and latest date the schedule mentions
+fn schedule_span(tasks: &[Task], data_date: Option<&str>) -> Option<(NaiveDate, NaiveDate)> {
+    let dates = tasks.iter()
+        .flat_map(|t| [
+            Some(&t.start), Some(&t.end), t.constraint_date.as_ref(),
+            t.deadline.as_ref(), t.actual_start.as_ref(), t.actual_finish.as_ref(),
+        ])
+        .flatten()
+        .map(String::as_str)
+        .chain(data_date)
+        .filter_map(parse_day);
+
+    dates.fold(None, |span, date| match span {
+        None => Some((date, date)),
+        Some((lo, hi)) => Some((lo.min(date), hi.max(date))),
+    })
+}
+
+/// Parse the day of a "YYYY-MM-DD" date or "YYYY-MM-DDTHH:MM" moment
+fn parse_day(date: &str) -> Option<NaiveDate> {
+    let day = date.split_once('T').map_or(date, |(day, _)| day);
+    NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_utils::{add_work_days, calc_work_days, calc_work_days_difference, work_day_on_or_before};
+    use serde_json::json;
+
+    fn day(date: &str) -> i32 {
+        parse_day(date).expect("valid date").num_days_from_ce()
+    }
+
+    fn date(day: i32) -> String {
+        NaiveDate::from_num_days_from_ce_opt(day).expect("valid day").to_string()
+    }
+
+    /// Monday-to-Friday calendar with a holiday, a shutdown and a worked Saturday
+    fn calendar() -> Calendar {
+        serde_json::from_value(json!({
+            "workingDays": [1, 2, 3, 4, 5],
+            "exceptions": {
+                "2024-03-01": "Holiday",
+                "2024-03-11": { "end": "2024-03-20", "description": "Shutdown" },
+                "2024-03-23": { "working": true },
+            },
+        }))
+        .expect("valid calendar")
+    }
+
+    #[test]
+    fn lookups_match_the_calendar_walkers() {
+        let calendar = calendar();
+        let index = DayIndex::build(&calendar, parse_day("2024-01-01").unwrap(), parse_day("2024-06-30").unwrap());
+        let days: Vec<i32> = (day("2024-02-15")..=day("2024-04-15")).collect();
+
+        for &d in &days {
+            for n in -15..=15 {
+                assert_eq!(
+                    index.add_work_days(d, n).map(date),
+                    add_work_days(&date(d), n, &calendar).ok(),
+                    "add {} to {}", n, date(d),
+                );
+            }
+            assert_eq!(index.work_day_on_or_before(d).map(date), work_day_on_or_before(&date(d), &calendar).ok(), "on or before {}", date(d));
+        }
+        for &start in days.iter().step_by(3) {
+            for &end in days.iter().step_by(4) {
+                let (from, to) = (date(start), date(end));
+                assert_eq!(index.work_days_difference(start, end), Some(calc_work_days_difference(&from, &to, &calendar)), "{} to {}", from, to);
+                assert_eq!(index.work_days_inclusive(start, end), Some(calc_work_days(&from, &to, &calendar)), "{} to {} inclusive", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn days_outside_the_table_fall_back() {
+        let index = DayIndex::build(&calendar(), parse_day("2024-01-01").unwrap(), parse_day("2024-01-31").unwrap());
+        assert_eq!(index.add_work_days(day("2023-12-29"), 1), None);
+        assert_eq!(index.add_work_days(day("2024-01-30"), 5), None);
+        assert_eq!(index.work_days_difference(day("2024-01-02"), day("2024-02-01")), None);
+    }
+}
@@ -0,0 +1,480 @@
+//! DCMA 14-point schedule assessment
+//!
+//! Runs the Defense Contract Management Agency's schedule quality checks over
+//! the tasks as last calculated. Activities are leaf tasks (summaries, blank
+//! and phantom rows excluded), and most checks only count the unfinished
+//! ones. Durations and floats are compared in work days (8 hours a day when
+//! scheduling in hours).
+//!
+//! Checks measured against the status date (invalid dates, missed tasks,
+//! CPLI, BEI) pass as not applicable when no data date is set.
+
+use crate::calendars::Calendars;
+use crate::cpm::{self, progress_state, Network, ProgressState};
+use crate::task_index::TaskIndex;
+use crate::types::{ConstraintType, DcmaCheck, DcmaMetric, LinkType, RowType, ScheduleOptions, SchedulingMode, Task, TimeUnit};
+use std::collections::HashSet;
+
+/// Work days beyond which float or a duration counts as high
+const HIGH_DAYS: i32 = 44;
+
+/// Share of activities (or links) most checks allow to fail, in percent
+const PERCENT_LIMIT: f64 = 5.0;
+
+/// Minimum share of finish-to-start links, in percent
+const FS_MINIMUM: f64 = 90.0;
+
+/// Minimum CPLI and BEI
+const INDEX_MINIMUM: f64 = 0.95;
+
+/// Work days a critical activity is delayed by for the critical path test
+const CPT_DELAY: i32 = 20;
+
+/// Run all 14 checks, in order
+///
+/// `opened_on` is only used to recalculate the critical path test (see
+/// `cpm::calculate`).
+pub fn assess(tasks: &[Task], index: &TaskIndex, calendars: &Calendars, options: &ScheduleOptions, data_date: Option<&str>, opened_on: Option<&str>) -> Vec<DcmaCheck> {
+    let day = match options.time_unit {
+        TimeUnit::Days => 1,
+        TimeUnit::Hours => 8,
+    };
+    let ids = |list: &[usize]| -> Vec<String> { list.iter().map(|&i| tasks[i].id.clone()).collect() };
+    let select = |from: &[usize], keep: &dyn Fn(&Task) -> bool| -> Vec<usize> {
+        from.iter().copied().filter(|&i| keep(&tasks[i])).collect()
+    };
+
+    let activities: Vec<usize> = (0..tasks.len())
+        .filter(|&i| matches!(tasks[i].row_type, None | Some(RowType::Task)) && index.children(&tasks[i].id).is_empty())
+        .collect();
+    let open = select(&activities, &|t| progress_state(t) != ProgressState::Complete);
+
+    // Links of unfinished activities to tasks that exist
+    let links: Vec<(usize, LinkType, i32)> = open.iter()
+        .flat_map(|&i| tasks[i].dependencies.iter()
+            .filter(|d| index.contains(&d.id))
+            .map(move |d| (i, d.link_type, d.lag)))
+        .collect();
+    let link_check = |number, metric, limit, keep: &dyn Fn(LinkType, i32) -> bool, what: &str| {
+        let matching: Vec<usize> = links.iter().filter(|(_, t, lag)| keep(*t, *lag)).map(|(i, _, _)| *i).collect();
+        let mut offenders = matching.clone();
+        offenders.dedup();
+        at_most(number, metric, matching.len(), links.len(), limit, ids(&offenders), what)
+    };
+
+    let mut checks = Vec::with_capacity(14);
+
+    // 1. Logic (a link into a summary counts as a successor too)
+    let has_successor: HashSet<usize> = tasks.iter()
+        .filter(|t| t.row_type != Some(RowType::Blank))
+        .flat_map(|t| t.dependencies.iter().filter_map(|d| index.get(&d.id)))
+        .collect();
+    let unlinked: Vec<usize> = open.iter().copied()
+        .filter(|&i| !tasks[i].dependencies.iter().any(|d| index.contains(&d.id)) || !has_successor.contains(&i))
+        .collect();
+    checks.push(at_most(1, DcmaMetric::Logic, unlinked.len(), open.len(), PERCENT_LIMIT, ids(&unlinked), "unfinished activities lack a predecessor or successor"));
+
+    // 2-4. Leads, lags, relationship types
+    checks.push(link_check(2, DcmaMetric::Leads, 0.0, &|_, lag| lag < 0, "links have a lead"));
+    checks.push(link_check(3, DcmaMetric::Lags, PERCENT_LIMIT, &|_, lag| lag > 0, "links have a lag"));
+    let mut other: Vec<usize> = links.iter().filter(|(_, t, _)| *t != LinkType::Fs).map(|(i, _, _)| *i).collect();
+    let fs = links.len() - other.len();
+    let fs_percent = if links.is_empty() { 100.0 } else { 100.0 * fs as f64 / links.len() as f64 };
+    other.dedup();
+    checks.push(DcmaCheck {
+        number: 4,
+        metric: DcmaMetric::RelationshipTypes,
+        passed: fs_percent >= FS_MINIMUM,
+        value: fs_percent,
+        threshold: FS_MINIMUM,
+        task_ids: ids(&other),
+        message: format!("{} of {} links are finish-to-start ({:.1}%, minimum {}%)", fs, links.len(), fs_percent, FS_MINIMUM),
+    });
+
+    // 5. Hard constraints
+    let hard = select(&open, &|t| matches!(t.constraint_type, ConstraintType::Mso | ConstraintType::Mfo | ConstraintType::Snlt | ConstraintType::Fnlt));
+    checks.push(at_most(5, DcmaMetric::HardConstraints, hard.len(), open.len(), PERCENT_LIMIT, ids(&hard), "unfinished activities have a hard constraint"));
+
+    // 6-7. High and negative float
+    let high_float = select(&open, &|t| t.total_float_days.is_some_and(|tf| tf > HIGH_DAYS * day));
+    checks.push(at_most(6, DcmaMetric::HighFloat, high_float.len(), open.len(), PERCENT_LIMIT, ids(&high_float), "unfinished activities have more than 44 days of float"));
+    let negative_float = select(&open, &|t| t.total_float_days.is_some_and(|tf| tf < 0));
+    checks.push(at_most(7, DcmaMetric::NegativeFloat, negative_float.len(), open.len(), 0.0, ids(&negative_float), "unfinished activities have negative float"));
+
+    // 8. High duration (baseline duration where there is one)
+    let long = select(&open, &|t| t.baseline_duration.unwrap_or(t.duration) > HIGH_DAYS * day);
+    checks.push(at_most(8, DcmaMetric::HighDuration, long.len(), open.len(), PERCENT_LIMIT, ids(&long), "unfinished activities last more than 44 days"));
+
+    // 9. Invalid dates: forecasts before the status date, actuals after it
+    checks.push(match data_date {
+        Some(status) => {
+            let status = day_of(status);
+            let invalid = select(&activities, &|t| {
+                let before = |d: &str| !d.is_empty() && day_of(d) < status;
+                let after = |d: &Option<String>| d.as_deref().is_some_and(|d| !d.is_empty() && day_of(d) > status);
+                let forecast_early = match progress_state(t) {
+                    ProgressState::NotStarted => before(&t.start) || before(&t.end),
+                    ProgressState::InProgress => before(&t.end),
+                    ProgressState::Complete => false,
+                };
+                forecast_early || after(&t.actual_start) || after(&t.actual_finish)
+            });
+            at_most(9, DcmaMetric::InvalidDates, invalid.len(), activities.len(), 0.0, ids(&invalid), "activities have forecast dates before, or actual dates after, the data date")
+        }
+        None => not_applicable(9, DcmaMetric::InvalidDates, 0.0),
+    });
+
+    // 10. Resources (trade partners)
+    let unassigned = select(&open, &|t| t.duration > 0 && t.trade_partner_ids.as_ref().is_none_or(Vec::is_empty));
+    checks.push(at_most(10, DcmaMetric::Resources, unassigned.len(), open.len(), 0.0, ids(&unassigned), "unfinished activities have no trade partner"));
+
+    // 11. Missed tasks: due by the status date per baseline, finished late or not at all
+    let due = data_date.map(|status| select(&activities, &|t| {
+        t.baseline_finish.as_deref().is_some_and(|bf| !bf.is_empty() && day_of(bf) <= day_of(status))
+    }));
+    let finished_by = |t: &Task, limit: &str| t.actual_finish.as_deref().is_some_and(|af| !af.is_empty() && day_of(af) <= day_of(limit));
+    checks.push(match (&due, data_date) {
+        (Some(due), Some(_)) if !due.is_empty() => {
+            let missed = select(due, &|t| !finished_by(t, t.baseline_finish.as_deref().unwrap_or_default()));
+            at_most(11, DcmaMetric::MissedTasks, missed.len(), due.len(), PERCENT_LIMIT, ids(&missed), "activities due by the data date finished late or not at all")
+        }
+        _ => not_applicable(11, DcmaMetric::MissedTasks, PERCENT_LIMIT),
+    });
+
+    // 12. Critical path test
+    checks.push(critical_path_test(tasks, index, calendars, options, data_date, opened_on, &open, day));
+
+    // 13. Critical path length index
+    checks.push(match data_date {
+        Some(status) => cpli(tasks, calendars, status, &activities, &open),
+        None => not_applicable(13, DcmaMetric::Cpli, INDEX_MINIMUM),
+    });
+
+    // 14. Baseline execution index
+    checks.push(match (&due, data_date) {
+        (Some(due), Some(status)) if !due.is_empty() => {
+            let done = select(due, &|t| finished_by(t, status)).len();
+            let late = select(due, &|t| !finished_by(t, status));
+            let bei = done as f64 / due.len() as f64;
+            DcmaCheck {
+                number: 14,
+                metric: DcmaMetric::Bei,
+                passed: bei >= INDEX_MINIMUM,
+                value: bei,
+                threshold: INDEX_MINIMUM,
+                task_ids: ids(&late),
+                message: format!("{} of {} activities due by the data date are finished (BEI {:.2}, minimum {})", done, due.len(), bei, INDEX_MINIMUM),
+            }
+        }
+        _ => not_applicable(14, DcmaMetric::Bei, INDEX_MINIMUM),
+    });
+
+    checks
+}
+
+/// Delay an unstarted critical activity and check the project finish slips
+/// by as much as the activity does
+#[allow(clippy::too_many_arguments)]
+fn critical_path_test(tasks: &[Task], index: &TaskIndex, calendars: &Calendars, options: &ScheduleOptions, data_date: Option<&str>, opened_on: Option<&str>, open: &[usize], day: i32) -> DcmaCheck {
+    let candidate = open.iter().copied().find(|&i| {
+        let t = &tasks[i];
+        t.is_critical == Some(true) && t.scheduling_mode == SchedulingMode::Auto && progress_state(t) == ProgressState::NotStarted
+    });
+    let delay = CPT_DELAY * day;
+    let Some(i) = candidate else {
+        return DcmaCheck {
+            message: "Not applicable: no unstarted critical activity to delay".to_string(),
+            ..not_applicable(12, DcmaMetric::CriticalPathTest, delay as f64)
+        };
+    };
+
+    // Calculate copies with and without the delay
+    let finish = |delay: i32| {
+        let mut copy = tasks.to_vec();
+        copy[i].duration += delay;
+        let mut network = Network::build(&copy, index);
+        let result = cpm::calculate(&mut copy, calendars, options, data_date, opened_on, &mut network, index, None);
+        (result.stats.project_end, copy[i].end.clone())
+    };
+    let (project_before, task_before) = finish(0);
+    let (project_after, task_after) = finish(delay);
+    let project = calendars.project();
+    let slip = |before: &str, after: &str| match (project.parse(before), project.parse(after)) {
+        (Some(before), Some(after)) => project.between(before, after),
+        _ => 0,
+    };
+    let project_slip = slip(&project_before, &project_after);
+    let task_slip = slip(&task_before, &task_after);
+
+    let passed = project_slip > 0 && project_slip == task_slip;
+    DcmaCheck {
+        number: 12,
+        metric: DcmaMetric::CriticalPathTest,
+        passed,
+        value: project_slip as f64,
+        threshold: delay as f64,
+        task_ids: if passed { Vec::new() } else { vec![tasks[i].id.clone()] },
+        message: format!(
+            "Delaying '{}' by {} moved its finish by {} and the project finish by {}",
+            tasks[i].id, delay, task_slip, project_slip,
+        ),
+    }
+}
+
+/// (critical path length + its float) / critical path length
+fn cpli(tasks: &[Task], calendars: &Calendars, status: &str, activities: &[usize], open: &[usize]) -> DcmaCheck {
+    let project_end = activities.iter()
+        .map(|&i| tasks[i].end.as_str())
+        .filter(|end| !end.is_empty())
+        .max()
+        .unwrap_or_default();
+    let project = calendars.project();
+    let length = match (project.parse(status), project.parse(project_end)) {
+        (Some(status), Some(project_end)) => project.between(status, project_end),
+        _ => 0,
+    };
+    if length <= 0 {
+        return not_applicable(13, DcmaMetric::Cpli, INDEX_MINIMUM);
+    }
+
+    let critical: Vec<usize> = open.iter().copied().filter(|&i| tasks[i].is_critical == Some(true)).collect();
+    let float = critical.iter().filter_map(|&i| tasks[i].total_float_days).min().unwrap_or(0);
+    let value = (length + float) as f64 / length as f64;
+    let passed = value >= INDEX_MINIMUM;
+    DcmaCheck {
+        number: 13,
+        metric: DcmaMetric::Cpli,
+        passed,
+        value,
+        threshold: INDEX_MINIMUM,
+        task_ids: if passed {
+            Vec::new()
+        } else {
+            critical.iter().filter(|&&i| tasks[i].total_float_days == Some(float)).map(|&i| tasks[i].id.clone()).collect()
+        },
+        message: format!("CPLI {:.2} (critical path {} days, float {}, minimum {})", value, length, float, INDEX_MINIMUM),
+    }
+}
+
+/// A check that at most `limit` percent of `total` count against it
+fn at_most(number: u8, metric: DcmaMetric, count: usize, total: usize, limit: f64, task_ids: Vec<String>, what: &str) -> DcmaCheck {
+    let value = if total == 0 { 0.0 } else { 100.0 * count as f64 / total as f64 };
+    DcmaCheck {
+        number,
+        metric,
+        passed: value <= limit,
+        value,
+        threshold: limit,
+        task_ids,
+        message: format!("{} of {} {} ({:.1}%, limit {}%)", count, total, what, value, limit),
+    }
+}
+
+/// A check that needs a data date (or due activities) the schedule lacks
+fn not_applicable(number: u8, metric: DcmaMetric, threshold: f64) -> DcmaCheck {
+    DcmaCheck {
+        number,
+        metric,
+        passed: true,
+        value: 0.0,
+        threshold,
+        task_ids: Vec::new(),
+        message: "Not applicable: no data date, or nothing due by it".to_string(),
+    }
+}
+
+/// The "YYYY-MM-DD" day of a date or moment
+fn day_of(date: &str) -> &str {
+    date.get(..10).unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{after, child, dep, task};
+    use crate::types::Calendar;
+    use std::collections::HashMap;
+
+    /// Calculate `tasks` on a Monday to Friday calendar (opened on Monday
+    /// 2024-01-08) and run the checks
+    fn assessed(mut tasks: Vec<Task>, data_date: Option<&str>) -> Vec<DcmaCheck> {
+        let calendar = Calendar { working_days: vec![1, 2, 3, 4, 5], ..Calendar::default() };
+        let named = HashMap::new();
+        let options = ScheduleOptions::default();
+        let calendars = Calendars::new(&calendar, &named, None, &options);
+        let index = TaskIndex::build(&tasks);
+        let mut network = Network::build(&tasks, &index);
+        cpm::calculate(&mut tasks, &calendars, &options, data_date, Some("2024-01-08"), &mut network, &index, None);
+        assess(&tasks, &index, &calendars, &options, data_date, Some("2024-01-08"))
+    }
+
+    /// Check `number` of `assessed`
+    fn check(tasks: Vec<Task>, data_date: Option<&str>, number: u8) -> DcmaCheck {
+        let check = assessed(tasks, data_date).swap_remove(number as usize - 1);
+        assert_eq!(check.number, number);
+        check
+    }
+
+    fn finished(id: &str, on: &str) -> Task {
+        Task { actual_start: Some(on.to_string()), actual_finish: Some(on.to_string()), ..task(id, 1) }
+    }
+
+    fn baseline(mut task: Task, finish: &str) -> Task {
+        task.baseline_finish = Some(finish.to_string());
+        task
+    }
+
+    #[test]
+    fn logic_counts_links_into_summaries_as_successors() {
+        let summary = after(task("P", 0), &["A"]);
+        let tasks = vec![task("Z", 1), after(task("A", 1), &["Z"]), summary, child("B", "P", 1), after(task("C", 1), &["B"])];
+
+        let logic = check(tasks, None, 1);
+        assert!(!logic.passed);
+        assert_eq!(logic.task_ids, ["Z", "B", "C"]);
+        assert_eq!(logic.message, "3 of 4 unfinished activities lack a predecessor or successor (75.0%, limit 5%)");
+    }
+
+    #[test]
+    fn leads_are_not_allowed() {
+        let mut lead = task("C", 1);
+        lead.dependencies.push(dep("B", LinkType::Fs, -1));
+        let leads = check(vec![task("A", 1), after(task("B", 1), &["A"]), lead], None, 2);
+        assert_eq!((leads.passed, leads.value), (false, 50.0));
+        assert_eq!(leads.task_ids, ["C"]);
+    }
+
+    #[test]
+    fn lags_are_allowed_on_up_to_five_percent_of_links() {
+        let chain = |lagged: usize| -> Vec<Task> {
+            (0..21).map(|i| {
+                let mut t = task(&format!("T{}", i), 1);
+                if i > 0 {
+                    t.dependencies.push(dep(&format!("T{}", i - 1), LinkType::Fs, if i <= lagged { 2 } else { 0 }));
+                }
+                t
+            }).collect()
+        };
+
+        let lags = check(chain(1), None, 3);
+        assert_eq!((lags.passed, lags.value), (true, 5.0));
+        let lags = check(chain(2), None, 3);
+        assert_eq!((lags.passed, lags.value), (false, 10.0));
+        assert_eq!(lags.task_ids, ["T1", "T2"]);
+    }
+
+    #[test]
+    fn relationship_types_need_ninety_percent_finish_to_start() {
+        let types = check(vec![task("A", 1), after(task("B", 1), &["A"])], None, 4);
+        assert_eq!((types.passed, types.value), (true, 100.0));
+
+        let mut ff = task("C", 1);
+        ff.dependencies.push(dep("B", LinkType::Ff, 0));
+        let types = check(vec![task("A", 1), after(task("B", 1), &["A"]), ff], None, 4);
+        assert_eq!((types.passed, types.value), (false, 50.0));
+        assert_eq!(types.task_ids, ["C"]);
+    }
+
+    #[test]
+    fn hard_constraints_on_finished_activities_do_not_count() {
+        let hard = |mut t: Task| {
+            t.constraint_type = ConstraintType::Mso;
+            t.constraint_date = Some("2024-01-08".to_string());
+            t
+        };
+        let constraints = check(vec![hard(task("A", 1)), hard(finished("B", "2024-01-08")), task("C", 1)], Some("2024-01-09"), 5);
+        assert_eq!((constraints.passed, constraints.value), (false, 50.0));
+        assert_eq!(constraints.task_ids, ["A"]);
+    }
+
+    #[test]
+    fn float_over_44_days_is_high() {
+        let high = check(vec![task("A", 50), task("B", 6), task("C", 5)], None, 6);
+        assert!(!high.passed);
+        assert_eq!(high.task_ids, ["C"]);
+    }
+
+    #[test]
+    fn any_negative_float_fails() {
+        let late = Task { deadline: Some("2024-01-10".to_string()), ..task("A", 5) };
+        let negative = check(vec![late, task("B", 1)], None, 7);
+        assert_eq!((negative.passed, negative.value), (false, 50.0));
+        assert_eq!(negative.task_ids, ["A"]);
+    }
+
+    #[test]
+    fn high_duration_prefers_the_baseline_duration() {
+        let long = check(vec![task("A", 45), task("B", 1)], None, 8);
+        assert_eq!(long.task_ids, ["A"]);
+
+        let rebaselined = Task { baseline_duration: Some(10), ..task("A", 45) };
+        let long = check(vec![rebaselined, task("B", 1)], None, 8);
+        assert!(long.passed);
+    }
+
+    #[test]
+    fn actual_dates_after_the_data_date_are_invalid() {
+        let tasks = vec![Task { actual_start: Some("2024-01-10".to_string()), ..task("A", 3) }, task("B", 1)];
+        let invalid = check(tasks.clone(), Some("2024-01-08"), 9);
+        assert!(!invalid.passed);
+        assert_eq!(invalid.task_ids, ["A"]);
+
+        assert!(check(tasks, None, 9).message.starts_with("Not applicable"));
+    }
+
+    #[test]
+    fn resources_skip_milestones() {
+        let assigned = Task { trade_partner_ids: Some(vec!["tp".to_string()]), ..task("B", 1) };
+        let resources = check(vec![task("A", 1), assigned, task("M", 0)], None, 10);
+        assert!(!resources.passed);
+        assert_eq!(resources.task_ids, ["A"]);
+    }
+
+    /// A finished on time, B due but unfinished, C not due yet
+    fn due_by_the_15th() -> Vec<Task> {
+        vec![
+            baseline(finished("A", "2024-01-10"), "2024-01-10"),
+            baseline(task("B", 1), "2024-01-12"),
+            baseline(task("C", 1), "2024-02-01"),
+        ]
+    }
+
+    #[test]
+    fn missed_tasks_are_those_due_and_not_finished_by_their_baseline() {
+        let missed = check(due_by_the_15th(), Some("2024-01-15"), 11);
+        assert_eq!((missed.passed, missed.value), (false, 50.0));
+        assert_eq!(missed.task_ids, ["B"]);
+
+        assert!(check(due_by_the_15th(), Some("2024-01-08"), 11).message.starts_with("Not applicable"));
+    }
+
+    #[test]
+    fn critical_path_test_delays_the_first_unstarted_critical_activity() {
+        let cpt = check(vec![task("A", 3), after(task("B", 2), &["A"])], None, 12);
+        assert_eq!((cpt.passed, cpt.value), (true, 20.0));
+        assert_eq!(cpt.message, "Delaying 'A' by 20 moved its finish by 20 and the project finish by 20");
+
+        let cpt = check(vec![finished("A", "2024-01-08")], Some("2024-01-09"), 12);
+        assert!(cpt.message.starts_with("Not applicable"));
+    }
+
+    #[test]
+    fn cpli_takes_the_float_of_the_critical_path() {
+        let cpli = check(vec![task("A", 10)], Some("2024-01-08"), 13);
+        assert_eq!((cpli.passed, cpli.value), (true, 1.0));
+
+        let late = Task { deadline: Some("2024-01-17".to_string()), ..task("A", 10) };
+        let cpli = check(vec![late], Some("2024-01-08"), 13);
+        assert!(!cpli.passed);
+        assert_eq!(cpli.task_ids, ["A"]);
+    }
+
+    #[test]
+    fn bei_is_the_share_of_due_activities_finished() {
+        let bei = check(due_by_the_15th(), Some("2024-01-15"), 14);
+        assert_eq!((bei.passed, bei.value), (false, 0.5));
+        assert_eq!(bei.task_ids, ["B"]);
+
+        assert!(check(due_by_the_15th(), None, 14).message.starts_with("Not applicable"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Errors returned by the `SchedulerEngine` JS API
+//!
+//! Every failing engine call throws a plain JS object
+//! `{ code, message, taskId?, field?, details? }` so callers can branch on
+//! `code` instead of matching message text.
+
+use serde::Serialize;
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+/// Kind of failure, serialized by name (e.g. "TaskNotFound")
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// `initialize` has not been called (or the engine was disposed)
+    NotInitialized,
+    /// No task with the given ID
+    TaskNotFound,
+    /// No named calendar with the given ID
+    CalendarNotFound,
+    /// A task could not be read (missing field, unknown link type, ...)
+    InvalidTask,
+    /// A field of a task update has the wrong type or value
+    InvalidField,
+    /// A batch operation could not be read
+    InvalidOperation,
+    /// A calendar could not be read or cannot be scheduled on
+    InvalidCalendar,
+    /// A date argument is not a valid date
+    InvalidDate,
+    /// Calculation options could not be read
+    InvalidOptions,
+    /// The change would make the task hierarchy circular
+    CycleDetected,
+    /// Loaded tasks have invalid parents (see `hierarchy.rs`)
+    InvalidHierarchy,
+    /// A result could not be converted to a JS value
+    SerializationFailed,
+}
+
+/// Structured error thrown to JavaScript
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerError {
+    pub code: ErrorCode,
+    /// Human-readable description
+    pub message: String,
+    /// Task the error concerns, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    /// Field the error concerns, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// One error per problem when several were found (e.g. per field)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<SchedulerError>,
+}
+
+impl SchedulerError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        SchedulerError { code, message: message.into(), task_id: None, field: None, details: Vec::new() }
+    }
+
+    /// Attach the task the error concerns
+    pub fn with_task(mut self, task_id: impl Into<String>) -> Self {
+        self.task_id = Some(task_id.into());
+        self
+    }
+
+    /// Attach the field the error concerns
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    pub fn not_initialized() -> Self {
+        SchedulerError::new(ErrorCode::NotInitialized, "Engine not initialized")
+    }
+
+    pub fn task_not_found(task_id: &str) -> Self {
+        SchedulerError::new(ErrorCode::TaskNotFound, format!("Task not found: {}", task_id)).with_task(task_id)
+    }
+
+    pub fn calendar_not_found(id: &str) -> Self {
+        SchedulerError::new(ErrorCode::CalendarNotFound, format!("Calendar not found: {}", id))
+    }
+
+    /// A value (result, tasks, ...) that could not be converted for JS
+    pub fn serialization(what: &str, error: impl fmt::Display) -> Self {
+        SchedulerError::new(ErrorCode::SerializationFailed, format!("Failed to serialize {}: {}", what, error))
+    }
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<SchedulerError> for JsValue {
+    fn from(error: SchedulerError) -> JsValue {
+        serde_wasm_bindgen::to_value(&error).unwrap_or_else(|_| JsValue::from_str(&error.message))
+    }
+}
@@ -0,0 +1,578 @@
+//! Dependency graph ordering for CPM passes
+//!
+//! Builds a topological order over the schedule network so the forward pass
+//! visits every task exactly once, after all of its predecessors, and the
+//! backward pass can walk the same order in reverse.
+//!
+//! Every leaf task is one node. A summary task is two nodes, so that logic
+//! can flow both into and out of it:
+//! - Summary start: receives the summary's predecessor links and drives all
+//!   of its children
+//! - Summary finish: the rollup of its children, which drives the summary's
+//!   successor links
+//!
+//! Edges are dependency links (predecessor -> successor), rollups
+//! (child -> parent finish) and drives (parent start -> child).
+//!
+//! The node-level inputs / outputs let incremental recalculation revisit
+//! only the steps whose inputs changed.
+//!
+//! Circular dependencies are found up front as strongly connected components
+//! (Tarjan). Links inside a cycle are excluded from the order so the rest of
+//! the schedule can still be calculated, and each cycle is reported as an
+//! ordered chain of task IDs.
+
+use crate::types::{CycleLink, DependencyCycle, Task};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Directed edge in the schedule network
+///
+/// Node `i` is task `i` (the finish node for a summary); node `n + i` is the
+/// start node of summary task `i`.
+#[derive(Clone, Copy)]
+struct Edge {
+    to: usize,
+    /// Index into the target task's `dependencies`, or None for a
+    /// structural (rollup / drive) edge
+    dependency: Option<usize>,
+}
+
+/// One step of a scheduling pass
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Visit {
+    /// Start node of a summary task: its predecessor logic, before its children
+    SummaryStart(usize),
+    /// A leaf task, or the finish (rollup) node of a summary task
+    Task(usize),
+}
+
+/// Topologically ordered view of the schedule network
+pub struct TaskGraph {
+    /// Task ID -> index into the task slice
+    pub index: HashMap<String, usize>,
+    /// Child task indices for each task index (blank rows excluded)
+    pub children: Vec<Vec<usize>>,
+    /// Parent task index for each task index (None for roots)
+    pub parent: Vec<Option<usize>>,
+    /// Scheduling steps in dependency order (blank rows excluded)
+    pub order: Vec<Visit>,
+    /// Circular dependency chains, one per strongly connected component
+    pub cycles: Vec<DependencyCycle>,
+    /// Outgoing edges for each node
+    successors: Vec<Vec<Edge>>,
+    /// Incoming nodes for each node
+    predecessors: Vec<Vec<usize>>,
+    /// Strongly connected component ID for each node
+    component: Vec<usize>,
+    /// True for tasks that belong to a circular dependency
+    in_cycle: Vec<bool>,
+    /// True for summary (parent) tasks
+    summary: Vec<bool>,
+}
+
+impl TaskGraph {
+    /// Build the graph, detect cycles and compute the topological order in O(N + E)
+    ///
+    /// Ties are broken by array order, so tasks without any relationship
+    /// between them keep their relative position.
+    pub fn build(tasks: &[Task], parent_ids: &HashSet<String>, blank_row_ids: &HashSet<String>) -> TaskGraph {
+        let n = tasks.len();
+        let index: HashMap<String, usize> = tasks.iter()
+            .enumerate()
+            .map(|(i, t)| (t.id.clone(), i))
+            .collect();
+        let summary: Vec<bool> = tasks.iter()
+            .map(|t| parent_ids.contains(&t.id) && !blank_row_ids.contains(&t.id))
+            .collect();
+        // Node that incoming logic attaches to
+        let entry = |i: usize| if summary[i] { n + i } else { i };
+
+        let mut successors: Vec<Vec<Edge>> = vec![Vec::new(); 2 * n];
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut parent: Vec<Option<usize>> = vec![None; n];
+
+        for (i, task) in tasks.iter().enumerate() {
+            if blank_row_ids.contains(&task.id) {
+                continue;
+            }
+
+            // Dependency links: predecessor (finish) -> this task (entry)
+            for (d, dep) in task.dependencies.iter().enumerate() {
+                if blank_row_ids.contains(&dep.id) {
+                    continue;
+                }
+                if let Some(&pred) = index.get(&dep.id) {
+                    successors[pred].push(Edge { to: entry(i), dependency: Some(d) });
+                }
+            }
+
+            // Summary start -> own finish (orders summaries without children)
+            if summary[i] {
+                successors[n + i].push(Edge { to: i, dependency: None });
+            }
+
+            // Rollup to parent finish, and parent start drives this task
+            if let Some(&p) = task.parent_id.as_ref().and_then(|pid| index.get(pid)) {
+                if summary[p] {
+                    successors[i].push(Edge { to: p, dependency: None });
+                    successors[n + p].push(Edge { to: entry(i), dependency: None });
+                    children[p].push(i);
+                    parent[i] = Some(p);
+                }
+            }
+        }
+
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); 2 * n];
+        for (from, edges) in successors.iter().enumerate() {
+            for edge in edges {
+                predecessors[edge.to].push(from);
+            }
+        }
+
+        let component = strongly_connected_components(&successors);
+
+        // A component is cyclic when a dependency link stays inside it;
+        // a task is on a cycle when either of its nodes is in such a component
+        let cyclic_components: HashSet<usize> = successors.iter()
+            .enumerate()
+            .flat_map(|(from, edges)| edges.iter().map(move |edge| (from, edge)))
+            .filter(|(from, edge)| is_cyclic_edge(&component, *from, edge))
+            .map(|(from, _)| component[from])
+            .collect();
+        let in_cycle: Vec<bool> = (0..n)
+            .map(|i| {
+                cyclic_components.contains(&component[i])
+                    || (summary[i] && cyclic_components.contains(&component[n + i]))
+            })
+            .collect();
+
+        let cycles = extract_cycles(tasks, &successors, &component, &cyclic_components);
+
+        // Kahn's algorithm over the network minus cyclic links, seeded in array order
+        let mut in_degree: Vec<usize> = vec![0; 2 * n];
+        for (from, edges) in successors.iter().enumerate() {
+            for edge in edges {
+                if !is_cyclic_edge(&component, from, edge) {
+                    in_degree[edge.to] += 1;
+                }
+            }
+        }
+
+        let is_node = |v: usize| {
+            let t = v % n;
+            !blank_row_ids.contains(&tasks[t].id) && (v < n || summary[t])
+        };
+        let mut queue: VecDeque<usize> = (n..2 * n).chain(0..n)
+            .filter(|&v| is_node(v) && in_degree[v] == 0)
+            .collect();
+        let mut order: Vec<Visit> = Vec::with_capacity(n);
+        let mut visited = vec![false; 2 * n];
+
+        while let Some(v) = queue.pop_front() {
+            visited[v] = true;
+            order.push(if v >= n { Visit::SummaryStart(v - n) } else { Visit::Task(v) });
+            for edge in &successors[v] {
+                if is_cyclic_edge(&component, v, edge) {
+                    continue;
+                }
+                in_degree[edge.to] -= 1;
+                if in_degree[edge.to] == 0 {
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+
+        // Only a corrupt hierarchy (parent cycle) can leave nodes unordered -
+        // append them in array order so every task still receives dates
+        for v in (n..2 * n).chain(0..n) {
+            if is_node(v) && !visited[v] {
+                order.push(if v >= n { Visit::SummaryStart(v - n) } else { Visit::Task(v) });
+            }
+        }
+
+        TaskGraph {
+            index,
+            children,
+            parent,
+            order,
+            cycles,
+            successors,
+            predecessors,
+            component,
+            in_cycle,
+            summary,
+        }
+    }
+
+    /// Whether the dependency link `pred -> succ` is part of a circular dependency
+    ///
+    /// Cyclic links are ignored by the scheduling passes.
+    pub fn is_cyclic_link(&self, pred: usize, succ: usize) -> bool {
+        let n = self.summary.len();
+        let entry = if self.summary[succ] { n + succ } else { succ };
+        self.component[pred] == self.component[entry]
+    }
+
+    /// Whether a task belongs to a circular dependency
+    pub fn is_in_cycle(&self, task: usize) -> bool {
+        self.in_cycle[task]
+    }
+
+    /// Whether a task is a summary (has a start node of its own)
+    pub fn is_summary(&self, task: usize) -> bool {
+        self.summary[task]
+    }
+
+    /// Number of nodes (two per task, see `node`)
+    pub fn node_count(&self) -> usize {
+        2 * self.summary.len()
+    }
+
+    /// Node of a scheduling step: the task index, or `n + index` for a
+    /// summary start
+    pub fn node(&self, visit: Visit) -> usize {
+        match visit {
+            Visit::Task(i) => i,
+            Visit::SummaryStart(p) => self.summary.len() + p,
+        }
+    }
+
+    /// Nodes a node is scheduled from in the forward pass
+    pub fn inputs(&self, node: usize) -> &[usize] {
+        &self.predecessors[node]
+    }
+
+    /// Nodes scheduled from a node in the forward pass (its inputs in the
+    /// backward pass)
+    pub fn outputs(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.successors[node].iter().map(|edge| edge.to)
+    }
+
+    /// Task indices in dependency order, skipping summary start steps
+    pub fn tasks_in_order(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.order.iter().filter_map(|visit| match visit {
+            Visit::Task(i) => Some(*i),
+            Visit::SummaryStart(_) => None,
+        })
+    }
+}
+
+/// Edges inside a component close a cycle (rollup edges never do on their own)
+fn is_cyclic_edge(component: &[usize], from: usize, edge: &Edge) -> bool {
+    edge.dependency.is_some() && component[from] == component[edge.to]
+}
+
+/// Tarjan's strongly connected components, iterative to keep long chains
+/// from overflowing the WASM stack
+///
+/// Returns the component ID for each node.
+fn strongly_connected_components(successors: &[Vec<Edge>]) -> Vec<usize> {
+    let n = successors.len();
+    let mut index: Vec<Option<usize>> = vec![None; n];
+    let mut low: Vec<usize> = vec![0; n];
+    let mut on_stack: Vec<bool> = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut component: Vec<usize> = vec![usize::MAX; n];
+    let mut next_index = 0;
+    let mut next_component = 0;
+
+    for root in 0..n {
+        if index[root].is_some() {
+            continue;
+        }
+
+        // (node, position of the next edge to explore)
+        let mut call_stack: Vec<(usize, usize)> = vec![(root, 0)];
+        index[root] = Some(next_index);
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (v, ref mut pos)) = call_stack.last_mut() {
+            if let Some(edge) = successors[v].get(*pos) {
+                *pos += 1;
+                let w = edge.to;
+                match index[w] {
+                    None => {
+                        index[w] = Some(next_index);
+                        low[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        call_stack.push((w, 0));
+                    }
+                    Some(w_index) if on_stack[w] => {
+                        low[v] = low[v].min(w_index);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(u, _)) = call_stack.last() {
+                low[u] = low[u].min(low[v]);
+            }
+
+            if Some(low[v]) == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component[w] = next_component;
+                    if w == v {
+                        break;
+                    }
+                }
+                next_component += 1;
+            }
+        }
+    }
+
+    component
+}
+
+/// Extract one ordered chain per cyclic component
+///
+/// Starting from the component's first node (array order), a breadth-first
+/// search inside the component finds the shortest chain that leads back to it.
+/// The start and finish nodes of a summary collapse into one entry.
+fn extract_cycles(tasks: &[Task], successors: &[Vec<Edge>], component: &[usize], cyclic_components: &HashSet<usize>) -> Vec<DependencyCycle> {
+    let n = tasks.len();
+    let mut cycles = Vec::new();
+    let mut seen_components: HashSet<usize> = HashSet::new();
+
+    for start in 0..successors.len() {
+        let comp = component[start];
+        if !cyclic_components.contains(&comp) || !seen_components.insert(comp) {
+            continue;
+        }
+
+        // came_from[node] = (previous node, edge taken)
+        let mut came_from: HashMap<usize, (usize, Edge)> = HashMap::new();
+        let mut queue: VecDeque<usize> = VecDeque::from([start]);
+        let mut closing: Option<(usize, Edge)> = None;
+
+        'search: while let Some(v) = queue.pop_front() {
+            for edge in &successors[v] {
+                if component[edge.to] != comp {
+                    continue;
+                }
+                if edge.to == start {
+                    closing = Some((v, *edge));
+                    break 'search;
+                }
+                if let std::collections::hash_map::Entry::Vacant(e) = came_from.entry(edge.to) {
+                    e.insert((v, *edge));
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+
+        let Some((last, closing_edge)) = closing else {
+            continue;
+        };
+
+        // Walk back from the closing edge to the start
+        let mut path: Vec<(usize, Edge)> = vec![(last, closing_edge)];
+        let mut node = last;
+        while node != start {
+            let (prev, edge) = came_from[&node];
+            path.push((prev, edge));
+            node = prev;
+        }
+        path.reverse();
+
+        // Summary start -> finish steps are internal to one task
+        path.retain(|(from, edge)| from % n != edge.to % n || edge.dependency.is_some());
+
+        cycles.push(DependencyCycle {
+            task_ids: path.iter().map(|(from, _)| tasks[from % n].id.clone()).collect(),
+            links: path.iter()
+                .map(|(from, edge)| CycleLink {
+                    from_id: tasks[from % n].id.clone(),
+                    to_id: tasks[edge.to % n].id.clone(),
+                    link_type: edge.dependency.map(|d| tasks[edge.to % n].dependencies[d].link_type),
+                })
+                .collect(),
+        });
+    }
+
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{after, child, task};
+    use crate::types::{LinkType, RowType};
+
+    fn build(tasks: &[Task]) -> TaskGraph {
+        let parent_ids: HashSet<String> = tasks.iter().filter_map(|t| t.parent_id.clone()).collect();
+        let blank_row_ids: HashSet<String> = tasks.iter()
+            .filter(|t| t.row_type == Some(RowType::Blank))
+            .map(|t| t.id.clone())
+            .collect();
+        TaskGraph::build(tasks, &parent_ids, &blank_row_ids)
+    }
+
+    /// Position of each step in the order
+    fn position(graph: &TaskGraph, visit: Visit) -> usize {
+        graph.order.iter().position(|&v| v == visit).expect("visit in order")
+    }
+
+    #[test]
+    fn predecessors_come_first_whatever_the_array_order() {
+        let tasks = vec![
+            after(task("C", 1), &["B"]),
+            after(task("B", 1), &["A"]),
+            task("A", 1),
+        ];
+        let graph = build(&tasks);
+
+        assert_eq!(graph.order, vec![Visit::Task(2), Visit::Task(1), Visit::Task(0)]);
+    }
+
+    #[test]
+    fn unrelated_tasks_keep_array_order() {
+        let tasks = vec![task("A", 1), task("B", 1), after(task("C", 1), &["A"]), task("D", 1)];
+        let graph = build(&tasks);
+
+        assert_eq!(graph.tasks_in_order().collect::<Vec<_>>(), vec![0, 1, 3, 2]);
+    }
+
+    #[test]
+    fn summary_start_precedes_children_and_finish_follows_them() {
+        let tasks = vec![
+            task("P", 0),
+            after(task("S", 0), &["P"]),
+            child("A", "S", 1),
+            after(child("B", "S", 1), &["A"]),
+            after(task("Q", 1), &["S"]),
+        ];
+        let graph = build(&tasks);
+
+        let start = position(&graph, Visit::SummaryStart(1));
+        let finish = position(&graph, Visit::Task(1));
+        assert!(position(&graph, Visit::Task(0)) < start);
+        assert!(start < position(&graph, Visit::Task(2)));
+        assert!(position(&graph, Visit::Task(2)) < position(&graph, Visit::Task(3)));
+        assert!(position(&graph, Visit::Task(3)) < finish);
+        assert!(finish < position(&graph, Visit::Task(4)));
+        assert_eq!(graph.children[1], vec![2, 3]);
+        assert_eq!(graph.parent[3], Some(1));
+    }
+
+    #[test]
+    fn blank_rows_are_left_out() {
+        let mut blank = after(task("X", 1), &["A"]);
+        blank.row_type = Some(RowType::Blank);
+        let tasks = vec![task("A", 1), blank, after(task("B", 1), &["X"])];
+        let graph = build(&tasks);
+
+        assert_eq!(graph.tasks_in_order().collect::<Vec<_>>(), vec![0, 2]);
+        assert!(graph.inputs(2).is_empty());
+    }
+
+    #[test]
+    fn every_task_is_visited_once() {
+        let tasks = vec![
+            task("S", 0),
+            child("A", "S", 1),
+            after(child("T", "S", 0), &["A"]),
+            after(child("B", "T", 1), &["A"]),
+            after(task("C", 1), &["T", "S"]),
+        ];
+        let graph = build(&tasks);
+
+        let mut tasks_seen: Vec<usize> = graph.tasks_in_order().collect();
+        tasks_seen.sort();
+        assert_eq!(tasks_seen, vec![0, 1, 2, 3, 4]);
+        // Plus one start step per summary
+        assert_eq!(graph.order.len(), 7);
+    }
+
+    #[test]
+    fn cycle_is_reported_once_as_an_ordered_chain() {
+        let tasks = vec![
+            task("Z", 1),
+            after(task("A", 1), &["C", "Z"]),
+            after(task("B", 1), &["A"]),
+            after(task("C", 1), &["B"]),
+            after(task("D", 1), &["C"]),
+        ];
+        let graph = build(&tasks);
+
+        assert_eq!(graph.cycles.len(), 1);
+        let cycle = &graph.cycles[0];
+        assert_eq!(cycle.task_ids, vec!["A", "B", "C"]);
+        let links: Vec<(&str, &str)> = cycle.links.iter().map(|l| (l.from_id.as_str(), l.to_id.as_str())).collect();
+        assert_eq!(links, vec![("A", "B"), ("B", "C"), ("C", "A")]);
+        assert!(cycle.links.iter().all(|l| l.link_type == Some(LinkType::Fs)));
+
+        let in_cycle: Vec<bool> = (0..5).map(|i| graph.is_in_cycle(i)).collect();
+        assert_eq!(in_cycle, vec![false, true, true, true, false]);
+        assert!(graph.is_cyclic_link(3, 1));
+        assert!(!graph.is_cyclic_link(0, 1));
+    }
+
+    #[test]
+    fn tasks_on_and_after_a_cycle_are_still_ordered() {
+        let tasks = vec![
+            after(task("A", 1), &["B"]),
+            after(task("B", 1), &["A"]),
+            after(task("C", 1), &["A", "B"]),
+        ];
+        let graph = build(&tasks);
+
+        assert_eq!(graph.tasks_in_order().collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn separate_cycles_are_reported_separately() {
+        let tasks = vec![
+            after(task("A", 1), &["B"]),
+            after(task("B", 1), &["A"]),
+            after(task("C", 1), &["B", "D"]),
+            after(task("D", 1), &["C"]),
+        ];
+        let graph = build(&tasks);
+
+        let chains: Vec<Vec<String>> = graph.cycles.iter().map(|c| c.task_ids.clone()).collect();
+        assert_eq!(chains, vec![vec!["A", "B"], vec!["C", "D"]]);
+    }
+
+    #[test]
+    fn self_link_is_a_cycle_of_one() {
+        let tasks = vec![task("A", 1), after(task("B", 1), &["B", "A"])];
+        let graph = build(&tasks);
+
+        assert_eq!(graph.cycles.len(), 1);
+        assert_eq!(graph.cycles[0].task_ids, vec!["B"]);
+        assert!(graph.is_in_cycle(1));
+        assert!(!graph.is_in_cycle(0));
+    }
+
+    #[test]
+    fn link_from_a_child_into_its_own_summary_is_a_cycle() {
+        // S drives its child A, and A is also a predecessor of S
+        let tasks = vec![after(task("S", 0), &["A"]), child("A", "S", 1)];
+        let graph = build(&tasks);
+
+        assert_eq!(graph.cycles.len(), 1);
+        let cycle = &graph.cycles[0];
+        assert_eq!(cycle.task_ids, vec!["A", "S"]);
+        // The structural edge from the summary to its child has no link type
+        let types: Vec<Option<LinkType>> = cycle.links.iter().map(|l| l.link_type).collect();
+        assert_eq!(types, vec![Some(LinkType::Fs), None]);
+        assert!(graph.is_in_cycle(0) && graph.is_in_cycle(1));
+    }
+
+    #[test]
+    fn rollups_alone_never_form_a_cycle() {
+        let tasks = vec![task("S", 0), child("A", "S", 1), after(child("B", "S", 1), &["A"])];
+        let graph = build(&tasks);
+
+        assert!(graph.cycles.is_empty());
+        assert!((0..3).all(|i| !graph.is_in_cycle(i)));
+    }
+}
@@ -0,0 +1,202 @@
+//! Schedule health
+//!
+//! Runs after the critical path is marked and rates every task, most severe
+//! status first (master spec 3.2):
+//! - Blocked: on a circular dependency, or links to a missing predecessor
+//! - Critical: negative float, or a no-later-than constraint slipped by more
+//!   than `health_slip_threshold`
+//! - At Risk: a constraint slipped by up to that threshold, or total float
+//!   below `health_float_threshold` (except zero float on the critical path)
+//! - Forced: manually scheduled, with none of the above
+//! - Healthy
+//!
+//! A slipped constraint makes the task's own float negative by the same
+//! amount. Float only counts as negative beyond that slip, so a short slip
+//! stays At Risk.
+
+use crate::calendars::Calendars;
+use crate::cpm::{progress_state, Dates, ProgressState};
+use crate::graph::TaskGraph;
+use crate::types::{ConstraintType, HealthIndicator, HealthReason, HealthStatus, ScheduleOptions, SchedulingMode, Task, TimeUnit};
+use std::collections::HashSet;
+
+/// A no-later-than constraint compared against the scheduled date
+struct ConstraintCheck {
+    reason: HealthReason,
+    /// Work units from the scheduled date to the constraint (negative = late)
+    variance: i32,
+    target: String,
+    projected: String,
+}
+
+/// Set `health` on every task (None for blank rows)
+pub fn assess_health(tasks: &mut [Task], dates: &[Dates], calendars: &Calendars, options: &ScheduleOptions, graph: &TaskGraph, blank_row_ids: &HashSet<String>) {
+    for (task, dates) in tasks.iter_mut().zip(dates) {
+        task.health = if blank_row_ids.contains(&task.id) {
+            None
+        } else {
+            Some(assess(task, dates, calendars, options, graph))
+        };
+    }
+}
+
+fn assess(task: &Task, dates: &Dates, calendars: &Calendars, options: &ScheduleOptions, graph: &TaskGraph) -> HealthIndicator {
+    let unit = match options.time_unit {
+        TimeUnit::Days => "days",
+        TimeUnit::Hours => "hours",
+    };
+    let mut found: Vec<(HealthStatus, HealthReason, String)> = Vec::new();
+
+    if task.in_cycle == Some(true) {
+        found.push((HealthStatus::Blocked, HealthReason::InCycle, "On a circular dependency".to_string()));
+    }
+    let missing: Vec<&str> = task.dependencies.iter()
+        .filter(|d| !graph.index.contains_key(&d.id))
+        .map(|d| d.id.as_str())
+        .collect();
+    if !missing.is_empty() {
+        found.push((HealthStatus::Blocked, HealthReason::MissingPredecessor, format!("Missing predecessor: {}", missing.join(", "))));
+    }
+
+    let check = check_constraint(task, dates, calendars);
+    let slip = check.as_ref().map_or(0, |c| (-c.variance).max(0));
+    if let Some(check) = check.as_ref().filter(|_| slip > 0) {
+        let status = if slip > options.health_slip_threshold { HealthStatus::Critical } else { HealthStatus::AtRisk };
+        let what = match check.reason {
+            HealthReason::FnltSlipped => "Finishes",
+            _ => "Starts",
+        };
+        found.push((status, check.reason, format!("{} {} {} after its constraint date {}", what, slip, unit, check.target)));
+    }
+
+    // Completed work has no float left to lose
+    if progress_state(task) != ProgressState::Complete {
+        match task.total_float_days {
+            Some(tf) if tf < 0 && -tf > slip => {
+                found.push((HealthStatus::Critical, HealthReason::NegativeFloat, format!("Negative float ({} {})", tf, unit)));
+            }
+            // Zero float is expected on the critical path, not a risk
+            Some(0) if task.is_critical == Some(true) => {}
+            Some(tf) if tf >= 0 && tf < options.health_float_threshold => {
+                found.push((HealthStatus::AtRisk, HealthReason::LowFloat, format!("Low float ({} {})", tf, unit)));
+            }
+            _ => {}
+        }
+    }
+
+    if task.scheduling_mode == SchedulingMode::Manual {
+        found.push((HealthStatus::Forced, HealthReason::ManualMode, "Manually scheduled - dates are not calculated".to_string()));
+    }
+
+    found.sort_by_key(|(status, _, _)| *status);
+    let status = found.first().map_or(HealthStatus::Healthy, |(status, _, _)| *status);
+    let icon = match status {
+        HealthStatus::Blocked => "🟣",
+        HealthStatus::Critical => "🔴",
+        HealthStatus::AtRisk => "🟡",
+        HealthStatus::Forced => "⚪",
+        HealthStatus::Healthy => "🟢",
+    };
+    let summary = found.first().map_or_else(|| "On track".to_string(), |(_, _, detail)| detail.clone());
+
+    HealthIndicator {
+        status,
+        reasons: found.iter().map(|(_, reason, _)| *reason).collect(),
+        icon: icon.to_string(),
+        summary,
+        details: found.into_iter().map(|(_, _, detail)| detail).collect(),
+        constraint_variance: check.as_ref().map(|c| c.variance),
+        constraint_target: check.as_ref().map(|c| c.target.clone()),
+        projected_date: check.map(|c| c.projected),
+    }
+}
+
+/// Compare a task's dates against its FNLT / SNLT constraint, if it has one
+fn check_constraint(task: &Task, dates: &Dates, calendars: &Calendars) -> Option<ConstraintCheck> {
+    let target = task.constraint_date.as_deref().filter(|d| !d.is_empty())?;
+    let calendar = calendars.task(task);
+    let (reason, projected, limit) = match task.constraint_type {
+        ConstraintType::Fnlt => (HealthReason::FnltSlipped, dates.end?, calendar.as_finish(target)?),
+        ConstraintType::Snlt => (HealthReason::ConstraintViolated, dates.start?, calendar.as_start(target)?),
+        _ => return None,
+    };
+
+    Some(ConstraintCheck {
+        reason,
+        variance: calendar.between(projected, limit),
+        target: target.to_string(),
+        projected: calendar.format(projected),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpm::{self, Network};
+    use crate::task_index::TaskIndex;
+    use crate::test_support::{after, task};
+    use crate::types::Calendar;
+    use std::collections::HashMap;
+
+    /// Calculate `tasks` on a Monday to Friday calendar from Monday
+    /// 2024-01-08 and give the status and reasons of each
+    fn health(mut tasks: Vec<Task>) -> Vec<(HealthStatus, Vec<HealthReason>)> {
+        let calendar = Calendar { working_days: vec![1, 2, 3, 4, 5], ..Calendar::default() };
+        let named = HashMap::new();
+        let options = ScheduleOptions::default();
+        let calendars = Calendars::new(&calendar, &named, None, &options);
+        let index = TaskIndex::build(&tasks);
+        let mut network = Network::build(&tasks, &index);
+        cpm::calculate(&mut tasks, &calendars, &options, Some("2024-01-08"), None, &mut network, &index, None);
+        tasks.into_iter()
+            .map(|t| t.health.map(|h| (h.status, h.reasons)).expect("health"))
+            .collect()
+    }
+
+    /// A task that must finish by Friday 2024-01-12
+    fn fnlt(id: &str, duration: i32) -> Task {
+        Task {
+            constraint_type: ConstraintType::Fnlt,
+            constraint_date: Some("2024-01-12".to_string()),
+            ..task(id, duration)
+        }
+    }
+
+    #[test]
+    fn the_most_severe_status_wins() {
+        // Blocked over Critical: a missing predecessor and a constraint 5 days late
+        let blocked = after(fnlt("A", 10), &["Gone"]);
+        // At Risk over Forced: manually scheduled with 1 day of float against A
+        let manual = Task {
+            scheduling_mode: SchedulingMode::Manual,
+            start: "2024-01-08".to_string(),
+            ..task("B", 9)
+        };
+        let statuses = health(vec![blocked, manual, task("C", 1)]);
+
+        assert_eq!(statuses[0], (HealthStatus::Blocked, vec![HealthReason::MissingPredecessor, HealthReason::FnltSlipped]));
+        assert_eq!(statuses[1], (HealthStatus::AtRisk, vec![HealthReason::LowFloat, HealthReason::ManualMode]));
+        assert_eq!(statuses[2], (HealthStatus::Healthy, vec![]));
+    }
+
+    #[test]
+    fn a_slip_beyond_the_threshold_is_critical() {
+        let statuses = health(vec![fnlt("A", 5), fnlt("B", 8), fnlt("C", 9)]);
+
+        assert_eq!(statuses[0], (HealthStatus::Healthy, vec![]));
+        assert_eq!(statuses[1], (HealthStatus::AtRisk, vec![HealthReason::FnltSlipped]));
+        assert_eq!(statuses[2], (HealthStatus::Critical, vec![HealthReason::FnltSlipped]));
+    }
+
+    #[test]
+    fn negative_float_counts_only_beyond_the_slip() {
+        // Slipped 3 days, so its float is -3 from the constraint alone
+        let slipped = fnlt("A", 8);
+        // The deadline takes 2 more days off
+        let missed = Task { deadline: Some("2024-01-10".to_string()), ..fnlt("B", 8) };
+        let statuses = health(vec![slipped, missed]);
+
+        assert_eq!(statuses[0], (HealthStatus::AtRisk, vec![HealthReason::FnltSlipped]));
+        assert_eq!(statuses[1], (HealthStatus::Critical, vec![HealthReason::NegativeFloat, HealthReason::FnltSlipped]));
+    }
+}
@@ -0,0 +1,290 @@
+//! Task hierarchy validation
+//!
+//! Every `parentId` is checked when tasks are loaded:
+//! - a task must not be its own parent
+//! - following parents must not lead back to the task (a parent cycle)
+//! - the parent must exist
+//! - the parent must not be a blank row
+//!
+//! Repairing re-attaches each task found at fault. Self-parented and
+//! orphaned tasks, and the first task of each cycle (in task order), move to
+//! the top level. Children of a blank row move up to its nearest ancestor
+//! that is not blank.
+
+use crate::task_index::TaskIndex;
+use crate::types::{HierarchyIssue, HierarchyIssueKind, RowType, Task};
+
+/// Find every problem in the hierarchy, in task order
+pub fn check_hierarchy(tasks: &[Task], index: &TaskIndex) -> Vec<HierarchyIssue> {
+    let mut found: Vec<(usize, HierarchyIssue)> = Vec::new();
+
+    for (i, task) in tasks.iter().enumerate() {
+        let Some(parent_id) = task.parent_id.as_deref() else {
+            continue;
+        };
+        let (kind, message) = if parent_id == task.id {
+            (HierarchyIssueKind::SelfParent, format!("Task '{}' is its own parent", task.id))
+        } else {
+            match index.get(parent_id) {
+                None => (HierarchyIssueKind::DanglingParent, format!("Task '{}' has parent '{}', which does not exist", task.id, parent_id)),
+                Some(p) if is_blank(&tasks[p]) => (HierarchyIssueKind::BlankParent, format!("Task '{}' has blank row '{}' as its parent", task.id, parent_id)),
+                Some(_) => continue,
+            }
+        };
+        found.push((i, issue(kind, task, Vec::new(), message)));
+    }
+
+    for cycle in parent_cycles(tasks, index) {
+        let task = &tasks[cycle[0]];
+        let chain: Vec<String> = cycle.iter().map(|&i| tasks[i].id.clone()).collect();
+        let message = format!("Parent cycle: {} -> {}", chain.join(" -> "), task.id);
+        found.push((cycle[0], issue(HierarchyIssueKind::ParentCycle, task, chain, message)));
+    }
+
+    found.sort_by_key(|(i, _)| *i);
+    found.into_iter().map(|(_, issue)| issue).collect()
+}
+
+/// Re-attach the task of each issue (see the module docs)
+///
+/// `index` must be the index of `tasks` before the repair; positions do not
+/// change, but its children lists are stale afterwards.
+pub fn repair_hierarchy(tasks: &mut [Task], index: &TaskIndex, issues: &mut [HierarchyIssue]) {
+    // Cut first, so the walks up from blank rows below cannot loop
+    for issue in issues.iter_mut().filter(|i| i.kind != HierarchyIssueKind::BlankParent) {
+        if let Some(i) = index.get(&issue.task_id) {
+            tasks[i].parent_id = None;
+            repaired(issue, None);
+        }
+    }
+
+    for issue in issues.iter_mut().filter(|i| i.kind == HierarchyIssueKind::BlankParent) {
+        let Some(i) = index.get(&issue.task_id) else {
+            continue;
+        };
+        let mut parent = tasks[i].parent_id.clone();
+        // Cycles are cut, so this ends within one step per task
+        for _ in 0..tasks.len() {
+            match parent.as_deref().and_then(|pid| index.get(pid)) {
+                Some(p) if is_blank(&tasks[p]) => parent = tasks[p].parent_id.clone(),
+                _ => break,
+            }
+        }
+        tasks[i].parent_id = parent.clone();
+        repaired(issue, parent.as_deref());
+    }
+}
+
+fn is_blank(task: &Task) -> bool {
+    task.row_type == Some(RowType::Blank)
+}
+
+fn issue(kind: HierarchyIssueKind, task: &Task, cycle: Vec<String>, message: String) -> HierarchyIssue {
+    HierarchyIssue {
+        kind,
+        task_id: task.id.clone(),
+        parent_id: task.parent_id.clone().unwrap_or_default(),
+        cycle,
+        repaired: false,
+        message,
+    }
+}
+
+/// Mark an issue repaired, saying where its task went
+fn repaired(issue: &mut HierarchyIssue, parent_id: Option<&str>) {
+    issue.repaired = true;
+    match parent_id {
+        Some(parent_id) => issue.message.push_str(&format!(" (moved under '{}')", parent_id)),
+        None => issue.message.push_str(" (moved to the top level)"),
+    }
+}
+
+/// Loops of two or more tasks made by parent links (self-parenting is
+/// reported on its own)
+///
+/// Each loop lists task positions child first, starting from its earliest
+/// task in task order.
+fn parent_cycles(tasks: &[Task], index: &TaskIndex) -> Vec<Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        OnChain,
+        Done,
+    }
+    let mut marks = vec![Mark::New; tasks.len()];
+    let mut cycles = Vec::new();
+
+    for start in 0..tasks.len() {
+        let mut chain: Vec<usize> = Vec::new();
+        let mut next = Some(start);
+        while let Some(i) = next {
+            match marks[i] {
+                Mark::Done => break,
+                Mark::OnChain => {
+                    let from = chain.iter().position(|&c| c == i).unwrap_or(0);
+                    let mut cycle = chain[from..].to_vec();
+                    if cycle.len() > 1 {
+                        let first = (0..cycle.len()).min_by_key(|&k| cycle[k]).unwrap_or(0);
+                        cycle.rotate_left(first);
+                        cycles.push(cycle);
+                    }
+                    break;
+                }
+                Mark::New => {
+                    marks[i] = Mark::OnChain;
+                    chain.push(i);
+                    next = index.parent(tasks, i);
+                }
+            }
+        }
+        for i in chain {
+            marks[i] = Mark::Done;
+        }
+    }
+
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+    use crate::load_hierarchy;
+    use crate::test_support::{child, task};
+    use crate::types::HierarchyPolicy;
+
+    fn blank(id: &str, parent_id: Option<&str>) -> Task {
+        Task { row_type: Some(RowType::Blank), parent_id: parent_id.map(str::to_string), ..task(id, 0) }
+    }
+
+    fn parents(tasks: &[Task]) -> Vec<(&str, Option<&str>)> {
+        tasks.iter().map(|t| (t.id.as_str(), t.parent_id.as_deref())).collect()
+    }
+
+    /// One task at fault of each kind, around a valid parent and child
+    fn faulty() -> Vec<Task> {
+        vec![
+            task("sum", 0),
+            child("ok", "sum", 1),
+            child("self", "self", 1),
+            child("c1", "c2", 1),
+            child("c2", "c3", 1),
+            child("c3", "c1", 1),
+            child("orphan", "gone", 1),
+            blank("b1", Some("sum")),
+            blank("b2", Some("b1")),
+            child("under_blanks", "b2", 1),
+            blank("b3", None),
+            child("under_top_blank", "b3", 1),
+        ]
+    }
+
+    #[test]
+    fn every_kind_of_issue_is_found_in_task_order() {
+        let tasks = faulty();
+        let issues = check_hierarchy(&tasks, &TaskIndex::build(&tasks));
+
+        let found: Vec<(HierarchyIssueKind, &str, &str)> = issues.iter()
+            .map(|i| (i.kind, i.task_id.as_str(), i.parent_id.as_str()))
+            .collect();
+        assert_eq!(found, vec![
+            (HierarchyIssueKind::SelfParent, "self", "self"),
+            (HierarchyIssueKind::ParentCycle, "c1", "c2"),
+            (HierarchyIssueKind::DanglingParent, "orphan", "gone"),
+            (HierarchyIssueKind::BlankParent, "b2", "b1"),
+            (HierarchyIssueKind::BlankParent, "under_blanks", "b2"),
+            (HierarchyIssueKind::BlankParent, "under_top_blank", "b3"),
+        ]);
+        assert_eq!(issues[1].cycle, vec!["c1", "c2", "c3"]);
+        assert_eq!(issues[1].message, "Parent cycle: c1 -> c2 -> c3 -> c1");
+        assert!(issues.iter().all(|i| !i.repaired));
+    }
+
+    #[test]
+    fn a_valid_hierarchy_has_no_issues() {
+        let tasks = vec![task("a", 0), child("b", "a", 0), child("c", "b", 1), blank("d", Some("a"))];
+        assert!(check_hierarchy(&tasks, &TaskIndex::build(&tasks)).is_empty());
+    }
+
+    #[test]
+    fn repair_moves_each_task_at_fault_and_nothing_else() {
+        let mut tasks = faulty();
+        let index = TaskIndex::build(&tasks);
+        let mut issues = check_hierarchy(&tasks, &index);
+        repair_hierarchy(&mut tasks, &index, &mut issues);
+
+        assert_eq!(parents(&tasks), vec![
+            ("sum", None),
+            ("ok", Some("sum")),
+            ("self", None),
+            // Only the first task of the cycle is cut loose
+            ("c1", None),
+            ("c2", Some("c3")),
+            ("c3", Some("c1")),
+            ("orphan", None),
+            ("b1", Some("sum")),
+            // Up past every blank row to the nearest one that is not
+            ("b2", Some("sum")),
+            ("under_blanks", Some("sum")),
+            ("b3", None),
+            ("under_top_blank", None),
+        ]);
+        assert!(issues.iter().all(|i| i.repaired));
+        assert_eq!(issues[2].message, "Task 'orphan' has parent 'gone', which does not exist (moved to the top level)");
+        assert_eq!(issues[4].message, "Task 'under_blanks' has blank row 'b2' as its parent (moved under 'sum')");
+
+        let index = TaskIndex::build(&tasks);
+        assert!(check_hierarchy(&tasks, &index).is_empty());
+    }
+
+    #[test]
+    fn a_blank_row_in_a_cycle_is_cut_before_walking_up() {
+        let mut tasks = vec![blank("b1", Some("b2")), blank("b2", Some("b1")), child("t", "b2", 1)];
+        let index = TaskIndex::build(&tasks);
+        let mut issues = check_hierarchy(&tasks, &index);
+        repair_hierarchy(&mut tasks, &index, &mut issues);
+
+        assert_eq!(parents(&tasks), vec![("b1", None), ("b2", None), ("t", None)]);
+    }
+
+    #[test]
+    fn the_reject_policy_leaves_the_tasks_alone_and_lists_every_issue() {
+        let mut tasks = faulty();
+        let error = load_hierarchy(&mut tasks, HierarchyPolicy::Reject).unwrap_err();
+
+        assert_eq!(error.code, ErrorCode::InvalidHierarchy);
+        assert_eq!(error.message, "Invalid task hierarchy: Task 'self' is its own parent (and 5 more)");
+        let details: Vec<(ErrorCode, Option<&str>)> = error.details.iter()
+            .map(|d| (d.code, d.task_id.as_deref()))
+            .collect();
+        assert_eq!(details, vec![
+            (ErrorCode::CycleDetected, Some("self")),
+            (ErrorCode::CycleDetected, Some("c1")),
+            (ErrorCode::InvalidHierarchy, Some("orphan")),
+            (ErrorCode::InvalidHierarchy, Some("b2")),
+            (ErrorCode::InvalidHierarchy, Some("under_blanks")),
+            (ErrorCode::InvalidHierarchy, Some("under_top_blank")),
+        ]);
+        assert_eq!(parents(&tasks), parents(&faulty()));
+    }
+
+    #[test]
+    fn the_repair_policy_loads_the_repaired_tasks() {
+        let mut tasks = faulty();
+        let issues = load_hierarchy(&mut tasks, HierarchyPolicy::Repair).unwrap();
+
+        assert_eq!(issues.len(), 6);
+        assert!(issues.iter().all(|i| i.repaired));
+        assert_eq!(tasks[2].parent_id, None);
+        assert_eq!(tasks[9].parent_id.as_deref(), Some("sum"));
+    }
+
+    #[test]
+    fn both_policies_accept_a_valid_hierarchy() {
+        for policy in [HierarchyPolicy::Reject, HierarchyPolicy::Repair] {
+            let mut tasks = vec![task("a", 0), child("b", "a", 1)];
+            assert!(load_hierarchy(&mut tasks, policy).unwrap().is_empty());
+            assert_eq!(tasks[1].parent_id.as_deref(), Some("a"));
+        }
+    }
+}
@@ -11,19 +11,54 @@
 //!
 //! await init();
 //! const engine = new SchedulerEngine();
-//! engine.initialize(tasks, calendar);
+//! engine.initialize(tasks, calendar, '2024-01-08'); // data date
 //! const result = engine.calculate();
 //! ```
+//!
+//! Failing calls throw a `SchedulerError` object
+//! (`{ code, message, taskId?, field?, details? }`, see `error.rs`).
 
 mod utils;
 mod types;
 mod cpm;
+mod calendars;
 mod date_utils;
+mod day_index;
+mod dcma;
+mod error;
+mod graph;
+mod health;
+mod hierarchy;
+mod logic;
+mod patch;
+mod task_index;
+#[cfg(test)]
+mod test_support;
 
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::prelude::*;
-use crate::types::{Task, Calendar, Dependency};
+use crate::calendars::Calendars;
+use crate::day_index::DayIndexes;
+use crate::task_index::TaskIndex;
+pub use crate::error::{ErrorCode, SchedulerError};
+use crate::types::{BatchOperation, BatchResult, CPMResult, HierarchyIssue, HierarchyIssueKind, HierarchyPolicy, ConstraintType, RowType, Task, Calendar, ScheduleOptions};
+
+/// Update fields that only change how a task is displayed - editing them
+/// does not require a recalculation
+const DISPLAY_FIELDS: &[&str] = &[
+    "name", "notes", "sortKey", "_collapsed", "tradePartnerIds", "wbs", "level",
+    "baselineStart", "baselineFinish", "baselineDuration",
+];
+
+/// Update fields that change the network itself (links, hierarchy, row type)
+const STRUCTURE_FIELDS: &[&str] = &["dependencies", "parentId", "rowType"];
+
+/// Update fields that change a task's progress state, which decides whether
+/// its predecessors still have to finish in time for it
+const PROGRESS_FIELDS: &[&str] = &["actualStart", "actualFinish"];
 
 // Import console.log for debugging
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
@@ -36,10 +71,17 @@ extern "C" {
     fn error(s: &str);
 }
 
+// Native builds (unit tests) have no console to log to
+#[cfg(not(target_arch = "wasm32"))]
+pub fn log(_s: &str) {}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn warn(_s: &str) {}
+
 /// Log macro for console output
 #[macro_export]
 macro_rules! console_log {
-    ($($t:tt)*) => (crate::log(&format_args!($($t)*).to_string()))
+    ($($t:tt)*) => ($crate::log(&format_args!($($t)*).to_string()))
 }
 
 /// The main scheduler engine exposed to JavaScript
@@ -49,7 +91,29 @@ macro_rules! console_log {
 #[wasm_bindgen]
 pub struct SchedulerEngine {
     tasks: Vec<Task>,
+    /// Task lookups by ID and by parent, kept in step with `tasks`
+    task_index: TaskIndex,
+    /// Problems found in the hierarchy of the last tasks loaded
+    hierarchy_issues: Vec<HierarchyIssue>,
     calendar: Option<Calendar>,
+    /// Named calendars tasks can work on instead (by `calendarId`)
+    calendars: HashMap<String, Calendar>,
+    /// Working-day tables of the calendars (None = build before calculating)
+    day_indexes: Option<DayIndexes>,
+    options: ScheduleOptions,
+    /// Project data (status) date - "YYYY-MM-DD"
+    data_date: Option<String>,
+    /// Day the tasks were loaded - the project start when there is no data
+    /// date and no task has an entered start
+    opened_on: Option<String>,
+    /// Network of the last calculation (None = build before calculating)
+    network: Option<cpm::Network>,
+    /// Links or hierarchy changed since the network was built
+    network_stale: bool,
+    /// Tasks edited since the last calculation
+    dirty: HashSet<String>,
+    /// Recalculate every task on the next calculation
+    recalc_all: bool,
     initialized: bool,
 }
 
@@ -62,7 +126,18 @@ impl SchedulerEngine {
         log("[WASM] SchedulerEngine created");
         SchedulerEngine {
             tasks: Vec::new(),
+            task_index: TaskIndex::default(),
+            hierarchy_issues: Vec::new(),
             calendar: None,
+            calendars: HashMap::new(),
+            day_indexes: None,
+            options: ScheduleOptions::default(),
+            data_date: None,
+            opened_on: None,
+            network: None,
+            network_stale: false,
+            dirty: HashSet::new(),
+            recalc_all: true,
             initialized: false,
         }
     }
@@ -72,217 +147,304 @@ impl SchedulerEngine {
     /// # Arguments
     /// * `tasks_val` - JavaScript array of Task objects
     /// * `calendar_val` - JavaScript Calendar object
+    /// * `data_date` - Optional project data date ("YYYY-MM-DD", or with a
+    ///   time of day when scheduling in hours)
+    /// 
+    /// Invalid parents are rejected or repaired according to the
+    /// `hierarchyPolicy` option (see `get_hierarchy_issues`).
     /// 
     /// # Returns
-    /// Ok(()) on success, or a JsValue error
-    pub fn initialize(&mut self, tasks_val: JsValue, calendar_val: JsValue) -> Result<(), JsValue> {
+    /// Ok(()) on success, or a SchedulerError
+    pub fn initialize(&mut self, tasks_val: JsValue, calendar_val: JsValue, data_date: Option<String>) -> Result<(), SchedulerError> {
         // Deserialize from JS objects
-        let tasks: Vec<Task> = serde_wasm_bindgen::from_value(tasks_val)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize tasks: {}", e)))?;
+        let mut tasks = deserialize_tasks(tasks_val)?;
         
         let calendar: Calendar = serde_wasm_bindgen::from_value(calendar_val)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize calendar: {}", e)))?;
+            .map_err(|e| SchedulerError::new(ErrorCode::InvalidCalendar, format!("Failed to deserialize calendar: {}", e)))?;
+        validate_calendar(&calendar, None)?;
+        
+        let data_date = validate_data_date(data_date)?;
+        let hierarchy_issues = load_hierarchy(&mut tasks, self.options.hierarchy_policy)?;
         
         self.tasks = tasks;
+        self.task_index = TaskIndex::build(&self.tasks);
+        self.hierarchy_issues = hierarchy_issues;
         self.calendar = Some(calendar);
+        self.data_date = data_date;
+        self.opened_on = Some(utils::today());
         self.initialized = true;
+        self.day_indexes = None;
+        self.invalidate();
         
         log(&format!("[WASM] Engine initialized with {} tasks", self.tasks.len()));
         Ok(())
     }
 
     /// Add a new task to the engine
-    pub fn add_task(&mut self, task_val: JsValue) -> Result<(), JsValue> {
+    pub fn add_task(&mut self, task_val: JsValue) -> Result<(), SchedulerError> {
         if !self.initialized {
-            return Err(JsValue::from_str("Engine not initialized"));
+            return Err(SchedulerError::not_initialized());
         }
         
-        let task: Task = serde_wasm_bindgen::from_value(task_val)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize task: {}", e)))?;
+        let task: Task = serde_wasm_bindgen::from_value(task_val.clone()).or_else(|e| {
+            // Retry through JSON to name the task in the error
+            let value: serde_json::Value = serde_wasm_bindgen::from_value(task_val)
+                .map_err(|_| SchedulerError::new(ErrorCode::InvalidTask, format!("Failed to deserialize task: {}", e)))?;
+            task_from_value(value)
+        })?;
         
-        self.tasks.push(task);
-        Ok(())
+        self.create_task(task)
     }
 
     /// Update an existing task
     /// 
+    /// Any field of a task can be updated; an explicit `null` clears an
+    /// optional field. The update is applied whole or not at all - a value
+    /// of the wrong type or out of range rejects it with one `details`
+    /// entry per field.
+    /// 
     /// # Arguments
     /// * `task_id` - ID of the task to update
     /// * `updates_val` - JavaScript object with fields to update
-    pub fn update_task(&mut self, task_id: String, updates_val: JsValue) -> Result<(), JsValue> {
+    pub fn update_task(&mut self, task_id: String, updates_val: JsValue) -> Result<(), SchedulerError> {
         if !self.initialized {
-            return Err(JsValue::from_str("Engine not initialized"));
+            return Err(SchedulerError::not_initialized());
         }
 
-        // Find the task
-        let task_index = self.tasks.iter().position(|t| t.id == task_id);
+        // Parse updates as JSON value to handle partial updates
+        let updates: serde_json::Value = serde_wasm_bindgen::from_value(updates_val)
+            .map_err(|e| SchedulerError::new(ErrorCode::InvalidField, format!("Failed to deserialize updates: {}", e)).with_task(&task_id))?;
         
-        if let Some(index) = task_index {
-            // Parse updates as JSON value to handle partial updates
-            let updates: serde_json::Value = serde_wasm_bindgen::from_value(updates_val)
-                .map_err(|e| JsValue::from_str(&format!("Failed to deserialize updates: {}", e)))?;
-            
-            // Apply updates to the task
-            let task = &mut self.tasks[index];
-            
-            if let Some(name) = updates.get("name").and_then(|v| v.as_str()) {
-                task.name = name.to_string();
-            }
-            if let Some(duration) = updates.get("duration").and_then(|v| v.as_i64()) {
-                task.duration = duration as i32;
-            }
-            if let Some(start) = updates.get("start").and_then(|v| v.as_str()) {
-                task.start = start.to_string();
-            }
-            if let Some(end) = updates.get("end").and_then(|v| v.as_str()) {
-                task.end = end.to_string();
-            }
-            if let Some(constraint_type) = updates.get("constraintType").and_then(|v| v.as_str()) {
-                task.constraint_type = constraint_type.to_string();
-            }
-            if let Some(constraint_date) = updates.get("constraintDate") {
-                task.constraint_date = constraint_date.as_str().map(|s| s.to_string());
-            }
-            if let Some(scheduling_mode) = updates.get("schedulingMode").and_then(|v| v.as_str()) {
-                task.scheduling_mode = scheduling_mode.to_string();
-            }
-            if let Some(progress) = updates.get("progress").and_then(|v| v.as_i64()) {
-                task.progress = progress as i32;
-            }
-            if let Some(notes) = updates.get("notes").and_then(|v| v.as_str()) {
-                task.notes = notes.to_string();
-            }
-            if let Some(parent_id) = updates.get("parentId") {
-                task.parent_id = parent_id.as_str().map(|s| s.to_string());
-            }
-            if let Some(sort_key) = updates.get("sortKey").and_then(|v| v.as_str()) {
-                task.sort_key = sort_key.to_string();
-            }
-            
-            // Handle dependencies update (CRITICAL - was missing!)
-            if let Some(deps_val) = updates.get("dependencies") {
-                match serde_json::from_value::<Vec<Dependency>>(deps_val.clone()) {
-                    Ok(new_deps) => {
-                        task.dependencies = new_deps;
-                    }
-                    Err(e) => {
-                        warn(&format!("[WASM] Failed to parse dependencies: {}", e));
-                    }
-                }
-            }
-            
-            // Handle _collapsed state
-            if let Some(collapsed) = updates.get("_collapsed").and_then(|v| v.as_bool()) {
-                task.collapsed = Some(collapsed);
-            }
-            
-            // Handle rowType
-            if let Some(row_type) = updates.get("rowType").and_then(|v| v.as_str()) {
-                task.row_type = Some(row_type.to_string());
-            }
-            
-            // Handle actualStart
-            if let Some(actual_start) = updates.get("actualStart") {
-                task.actual_start = actual_start.as_str().map(|s| s.to_string());
-            }
-            
-            // Handle actualFinish
-            if let Some(actual_finish) = updates.get("actualFinish") {
-                task.actual_finish = actual_finish.as_str().map(|s| s.to_string());
-            }
-            
-            // Handle remainingDuration
-            if let Some(remaining) = updates.get("remainingDuration").and_then(|v| v.as_i64()) {
-                task.remaining_duration = Some(remaining as i32);
-            }
-            
-            // Handle tradePartnerIds
-            if let Some(partner_ids) = updates.get("tradePartnerIds") {
-                match serde_json::from_value::<Vec<String>>(partner_ids.clone()) {
-                    Ok(ids) => {
-                        task.trade_partner_ids = Some(ids);
-                    }
-                    Err(_) => {
-                        // If parsing fails, try to handle null
-                        if partner_ids.is_null() {
-                            task.trade_partner_ids = None;
-                        }
-                    }
-                }
-            }
-            
-            Ok(())
-        } else {
-            Err(JsValue::from_str(&format!("Task not found: {}", task_id)))
-        }
+        self.patch_task(&task_id, &updates)
     }
 
-    /// Delete a task by ID
-    pub fn delete_task(&mut self, task_id: String) -> Result<(), JsValue> {
+    /// Delete a task by ID, along with every task below it
+    pub fn delete_task(&mut self, task_id: String) -> Result<(), SchedulerError> {
         if !self.initialized {
-            return Err(JsValue::from_str("Engine not initialized"));
+            return Err(SchedulerError::not_initialized());
         }
 
-        let original_len = self.tasks.len();
-        self.tasks.retain(|t| t.id != task_id);
+        self.remove_task(&task_id)
+    }
+
+    /// Apply a list of create / update / delete / move operations atomically
+    /// 
+    /// Operations run in order, so later ones can refer to tasks created by
+    /// earlier ones. If any operation fails, none of them are applied and the
+    /// error names the failing operation.
+    /// 
+    /// # Arguments
+    /// * `operations_val` - Array of `{ op: "create", task }`,
+    ///   `{ op: "update", id, updates }`, `{ op: "delete", id }` or
+    ///   `{ op: "move", id, parentId, sortKey? }`
+    /// * `recalculate` - Run `calculate()` once the batch is applied
+    /// * `data_date` - Data date for that calculation (see `calculate`)
+    /// 
+    /// # Returns
+    /// `{ created, updated, deleted, result? }` - task IDs by kind of change
+    /// and, when recalculating, the calculation result
+    pub fn apply_batch(&mut self, operations_val: JsValue, recalculate: bool, data_date: Option<String>) -> Result<JsValue, SchedulerError> {
+        if !self.initialized {
+            return Err(SchedulerError::not_initialized());
+        }
         
-        if self.tasks.len() == original_len {
-            Err(JsValue::from_str(&format!("Task not found: {}", task_id)))
-        } else {
-            Ok(())
+        let values: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(operations_val)
+            .map_err(|e| SchedulerError::new(ErrorCode::InvalidOperation, format!("Failed to deserialize operations: {}", e)))?;
+        let operations = values.into_iter()
+            .enumerate()
+            .map(|(i, value)| parse_operation(i, value))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data_date = validate_data_date(data_date)?;
+        
+        let mut changes = self.apply_operations(operations)?;
+        log(&format!("[WASM] Applied batch of {} operations", changes.created.len() + changes.updated.len() + changes.deleted.len()));
+        
+        if recalculate {
+            changes.result = Some(self.run_calculation(data_date)?);
         }
+        
+        serde_wasm_bindgen::to_value(&changes)
+            .map_err(|e| SchedulerError::serialization("batch result", e))
     }
 
     /// Sync all tasks (bulk replace)
-    pub fn sync_tasks(&mut self, tasks_val: JsValue) -> Result<(), JsValue> {
-        let tasks: Vec<Task> = serde_wasm_bindgen::from_value(tasks_val)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize tasks: {}", e)))?;
+    /// 
+    /// The hierarchy is checked as in `initialize`.
+    pub fn sync_tasks(&mut self, tasks_val: JsValue) -> Result<(), SchedulerError> {
+        let mut tasks = deserialize_tasks(tasks_val)?;
+        let hierarchy_issues = load_hierarchy(&mut tasks, self.options.hierarchy_policy)?;
         
         self.tasks = tasks;
+        self.task_index = TaskIndex::build(&self.tasks);
+        self.hierarchy_issues = hierarchy_issues;
+        self.day_indexes = None;
+        self.invalidate();
         log(&format!("[WASM] Synced {} tasks", self.tasks.len()));
         Ok(())
     }
 
     /// Update calendar configuration
-    pub fn update_calendar(&mut self, calendar_val: JsValue) -> Result<(), JsValue> {
+    pub fn update_calendar(&mut self, calendar_val: JsValue) -> Result<(), SchedulerError> {
         let calendar: Calendar = serde_wasm_bindgen::from_value(calendar_val)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize calendar: {}", e)))?;
+            .map_err(|e| SchedulerError::new(ErrorCode::InvalidCalendar, format!("Failed to deserialize calendar: {}", e)))?;
+        validate_calendar(&calendar, None)?;
         
         self.calendar = Some(calendar);
+        self.day_indexes = None;
+        self.invalidate();
         log("[WASM] Calendar updated");
         Ok(())
     }
 
+    /// Add or replace a named calendar
+    ///
+    /// Tasks whose `calendarId` matches `id` are scheduled on it.
+    pub fn set_calendar(&mut self, id: String, calendar_val: JsValue) -> Result<(), SchedulerError> {
+        let calendar: Calendar = serde_wasm_bindgen::from_value(calendar_val)
+            .map_err(|e| SchedulerError::new(ErrorCode::InvalidCalendar, format!("Failed to deserialize calendar '{}': {}", id, e)))?;
+        validate_calendar(&calendar, Some(&id))?;
+        
+        log(&format!("[WASM] Calendar '{}' set", id));
+        self.calendars.insert(id, calendar);
+        self.day_indexes = None;
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Remove a named calendar - its tasks fall back to the project calendar
+    pub fn remove_calendar(&mut self, id: String) -> Result<(), SchedulerError> {
+        if self.calendars.remove(&id).is_none() {
+            return Err(SchedulerError::calendar_not_found(&id));
+        }
+        
+        self.day_indexes = None;
+        self.invalidate();
+        log(&format!("[WASM] Calendar '{}' removed", id));
+        Ok(())
+    }
+
+    /// List a calendar's exception days between two dates (inclusive)
+    ///
+    /// Recurring rules are expanded into concrete dates for display. Uses the
+    /// named calendar `calendar_id`, or the project calendar when omitted.
+    /// Returns an array of `{ date, working, description }`.
+    pub fn get_calendar_exceptions(&self, start: String, end: String, calendar_id: Option<String>) -> Result<JsValue, SchedulerError> {
+        let calendar = match &calendar_id {
+            Some(id) => self.calendars.get(id)
+                .ok_or_else(|| SchedulerError::calendar_not_found(id))?,
+            None => self.calendar.as_ref()
+                .ok_or_else(|| SchedulerError::new(ErrorCode::NotInitialized, "Calendar not initialized"))?,
+        };
+        let parse = |date: &str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| SchedulerError::new(ErrorCode::InvalidDate, format!("Invalid date '{}': {}", date, e)));
+        
+        let days = date_utils::expand_exceptions(calendar, parse(&start)?, parse(&end)?);
+        serde_wasm_bindgen::to_value(&days)
+            .map_err(|e| SchedulerError::serialization("exceptions", e))
+    }
+
+    /// Update calculation options (progress mode, ...)
+    ///
+    /// Passing `undefined` or `null` restores the defaults.
+    pub fn set_options(&mut self, options_val: JsValue) -> Result<(), SchedulerError> {
+        let options: ScheduleOptions = if options_val.is_undefined() || options_val.is_null() {
+            ScheduleOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options_val)
+                .map_err(|e| SchedulerError::new(ErrorCode::InvalidOptions, format!("Failed to deserialize options: {}", e)))?
+        };
+        
+        self.options = options;
+        self.invalidate();
+        log("[WASM] Options updated");
+        Ok(())
+    }
+
     /// Run CPM calculation and return results
     /// 
+    /// Only the tasks reachable from edits made since the last calculation
+    /// are recalculated. Initializing, syncing, a new calendar, new options
+    /// or a new data date recalculate everything.
+    /// 
+    /// # Arguments
+    /// * `data_date` - Optional new project data date ("YYYY-MM-DD");
+    ///   when omitted the data date from the last call is reused. With no
+    ///   data date at all, the project starts at the earliest actual start
+    ///   or SNET/MSO date, or else on the day the tasks were loaded.
+    /// 
     /// # Returns
     /// A JavaScript object containing:
-    /// - `tasks`: Array of tasks with calculated dates
+    /// - `tasks`: Tasks whose values changed (all tasks when `full` is true)
     /// - `stats`: Calculation statistics
-    pub fn calculate(&mut self) -> Result<JsValue, JsValue> {
-        if !self.initialized {
-            return Err(JsValue::from_str("Engine not initialized"));
-        }
-
-        let calendar = self.calendar.as_ref()
-            .ok_or_else(|| JsValue::from_str("Calendar not initialized"))?;
+    /// - `cycles`: Circular dependency chains (tasks on them are flagged `_inCycle`)
+    /// - `full`: Whether every task was recalculated
+    pub fn calculate(&mut self, data_date: Option<String>) -> Result<JsValue, SchedulerError> {
+        let result = self.run_calculation(data_date)?;
         
-        // Run CPM calculation
-        let result = cpm::calculate(&mut self.tasks, calendar);
+        // Convert result to JsValue
+        serde_wasm_bindgen::to_value(&result)
+            .map_err(|e| SchedulerError::serialization("result", e))
+    }
+
+    /// Get the current project data date, if one has been set
+    pub fn data_date(&self) -> Option<String> {
+        self.data_date.clone()
+    }
+
+    /// Get the driving path back from a task
+    ///
+    /// Uses the driving logic recorded by the last `calculate()`. Returns an
+    /// array of steps, starting with the chosen task.
+    pub fn get_driving_path(&self, task_id: String) -> Result<JsValue, SchedulerError> {
+        if !self.task_index.contains(&task_id) {
+            return Err(SchedulerError::task_not_found(&task_id));
+        }
         
-        // Update internal tasks with calculated values
-        self.tasks = result.tasks.clone();
+        let path = cpm::driving_path(&self.tasks, &self.task_index, &task_id);
+        serde_wasm_bindgen::to_value(&path)
+            .map_err(|e| SchedulerError::serialization("driving path", e))
+    }
+
+    /// Check every dependency link for problems
+    ///
+    /// Reports links the calculation skips (to missing tasks or blank rows)
+    /// and likely mistakes: self links, duplicate links, links between a
+    /// task and its own summary, and lags longer than the task. Returns an
+    /// array of `{ kind, taskId, predecessorId, linkType, lag, message }`.
+    pub fn validate_logic(&self) -> Result<JsValue, SchedulerError> {
+        let issues = logic::validate_logic(&self.tasks, &self.task_index);
+        serde_wasm_bindgen::to_value(&issues)
+            .map_err(|e| SchedulerError::serialization("logic issues", e))
+    }
+
+    /// Run the DCMA 14-point schedule assessment over the tasks
+    ///
+    /// Uses the dates and floats of the last `calculate()` and the current
+    /// data date; the critical path test recalculates copies of the tasks.
+    /// Returns an array of `{ number, metric, passed, value, threshold,
+    /// taskIds, message }`, one per check.
+    pub fn dcma_check(&self) -> Result<JsValue, SchedulerError> {
+        if !self.initialized {
+            return Err(SchedulerError::not_initialized());
+        }
         
-        log(&format!(
-            "[WASM] CPM complete: {} tasks, {} critical, {:.2}ms",
-            result.stats.task_count,
-            result.stats.critical_count,
-            result.stats.calc_time
-        ));
+        let calendar = self.calendar.as_ref()
+            .ok_or_else(|| SchedulerError::new(ErrorCode::NotInitialized, "Calendar not initialized"))?;
+        let calendars = Calendars::new(calendar, &self.calendars, self.day_indexes.as_ref(), &self.options);
+        let checks = dcma::assess(&self.tasks, &self.task_index, &calendars, &self.options, self.data_date.as_deref(), self.opened_on.as_deref());
         
-        // Convert result to JsValue
-        serde_wasm_bindgen::to_value(&result)
-            .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+        log(&format!("[WASM] DCMA check: {} of {} passed", checks.iter().filter(|c| c.passed).count(), checks.len()));
+        serde_wasm_bindgen::to_value(&checks)
+            .map_err(|e| SchedulerError::serialization("DCMA checks", e))
+    }
+
+    /// Get the hierarchy problems found when the tasks were last loaded
+    ///
+    /// Returns an array of `{ kind, taskId, parentId, cycle?, repaired,
+    /// message }`; empty when the hierarchy was valid.
+    pub fn get_hierarchy_issues(&self) -> Result<JsValue, SchedulerError> {
+        serde_wasm_bindgen::to_value(&self.hierarchy_issues)
+            .map_err(|e| SchedulerError::serialization("hierarchy issues", e))
     }
 
     /// Get current task count
@@ -296,20 +458,443 @@ impl SchedulerEngine {
     }
 
     /// Get all tasks as JavaScript array
-    pub fn get_tasks(&self) -> Result<JsValue, JsValue> {
+    pub fn get_tasks(&self) -> Result<JsValue, SchedulerError> {
         serde_wasm_bindgen::to_value(&self.tasks)
-            .map_err(|e| JsValue::from_str(&format!("Failed to serialize tasks: {}", e)))
+            .map_err(|e| SchedulerError::serialization("tasks", e))
     }
 
     /// Dispose and free resources
     pub fn dispose(&mut self) {
         self.tasks.clear();
+        self.task_index = TaskIndex::default();
+        self.hierarchy_issues.clear();
         self.calendar = None;
+        self.calendars.clear();
+        self.day_indexes = None;
+        self.data_date = None;
+        self.opened_on = None;
+        self.invalidate();
         self.initialized = false;
         log("[WASM] Engine disposed");
     }
 }
 
+impl SchedulerEngine {
+    /// Apply the operations of a batch (see `apply_batch`), all or none
+    fn apply_operations(&mut self, operations: Vec<BatchOperation>) -> Result<BatchResult, SchedulerError> {
+        // Restored if any operation fails
+        let snapshot = (self.tasks.clone(), self.dirty.clone(), self.network_stale);
+        let mut changes = BatchResult::default();
+        let mut created = Vec::new();
+        let mut failure = None;
+        for (i, operation) in operations.into_iter().enumerate() {
+            let kind = operation.kind();
+            match self.apply_operation(operation) {
+                Ok(id) => match kind {
+                    "create" => {
+                        created.push((i, id.clone()));
+                        changes.created.push(id);
+                    }
+                    "delete" => changes.deleted.push(id),
+                    _ => changes.updated.push(id),
+                },
+                Err(e) => {
+                    failure = Some((i, kind, e));
+                    break;
+                }
+            }
+        }
+        // A created task can come before its parent (pasted children), so
+        // parents are checked once every operation is applied
+        if failure.is_none() {
+            failure = created.iter()
+                .find_map(|(i, id)| self.check_parent(id, "added").err().map(|e| (*i, "create", e)));
+        }
+        if let Some((i, kind, e)) = failure {
+            (self.tasks, self.dirty, self.network_stale) = snapshot;
+            self.task_index = TaskIndex::build(&self.tasks);
+            return Err(SchedulerError { message: format!("Operation {} ({}): {}", i, kind, e.message), ..e });
+        }
+        Ok(changes)
+    }
+    
+    /// Add a task whose parent is already in the engine
+    fn create_task(&mut self, task: Task) -> Result<(), SchedulerError> {
+        if let Some(error) = task.parent_id.as_deref().and_then(|parent_id| self.parent_error(&task.id, parent_id, "added")) {
+            return Err(error);
+        }
+        self.insert_task(task)
+    }
+    
+    /// Add a task and track what it touches
+    fn insert_task(&mut self, task: Task) -> Result<(), SchedulerError> {
+        if self.task_index.contains(&task.id) {
+            return Err(SchedulerError::new(ErrorCode::InvalidTask, format!("Task already exists: {}", task.id))
+                .with_task(&task.id)
+                .with_field("id"));
+        }
+        
+        self.task_index.push(&task, self.tasks.len());
+        self.tasks.push(task);
+        self.mark_structure_dirty(self.tasks.len() - 1);
+        self.network_stale = true;
+        Ok(())
+    }
+    
+    /// Apply a patch to a task (see `patch.rs`) and track what it touches
+    fn patch_task(&mut self, task_id: &str, updates: &serde_json::Value) -> Result<(), SchedulerError> {
+        let Some(index) = self.task_index.get(task_id) else {
+            return Err(SchedulerError::task_not_found(task_id));
+        };
+        
+        if let Some(parent_id) = updates.get("parentId").and_then(|v| v.as_str()) {
+            if let Some(error) = self.parent_error(task_id, parent_id, "moved") {
+                return Err(error);
+            }
+        }
+        
+        let mut task = self.tasks[index].clone();
+        let fields = patch::apply_patch(&mut task, updates)
+            .map_err(|errors| invalid_patch(task_id, errors))?;
+        
+        // Track what the next calculation has to revisit
+        let structural = fields.iter().any(|f| STRUCTURE_FIELDS.contains(&f.as_str()));
+        // An ALAP task (and the summaries above it) is stored at its ALAP
+        // position, not the early dates its successors were scheduled from
+        let alap_toggled = (self.tasks[index].constraint_type == ConstraintType::Alap) != (task.constraint_type == ConstraintType::Alap);
+        if structural || alap_toggled {
+            self.mark_structure_dirty(index);
+            self.network_stale |= structural;
+        } else if fields.iter().any(|f| f == "calendarId" || PROGRESS_FIELDS.contains(&f.as_str())) {
+            // Lags on this task's links can follow its calendar, and its
+            // progress decides whether its predecessors still drive it
+            self.mark_links_dirty(index);
+        } else if fields.iter().any(|f| !DISPLAY_FIELDS.contains(&f.as_str())) {
+            self.dirty.insert(task_id.to_string());
+        }
+        
+        self.task_index.reparent(index, self.tasks[index].parent_id.as_deref(), task.parent_id.as_deref());
+        self.task_index.relink(index, &self.tasks[index].dependencies, &task.dependencies);
+        self.tasks[index] = task;
+        
+        // Old and new neighbours both need revisiting
+        if structural {
+            self.mark_structure_dirty(index);
+        }
+        
+        Ok(())
+    }
+    
+    /// Remove a task and the tasks below it, and track what they touched
+    fn remove_task(&mut self, task_id: &str) -> Result<(), SchedulerError> {
+        let Some(index) = self.task_index.get(task_id) else {
+            return Err(SchedulerError::task_not_found(task_id));
+        };
+        
+        self.mark_structure_dirty(index);
+        self.network_stale = true;
+        let mut removed = self.subtree(index);
+        removed.sort_unstable();
+        self.task_index.remove(&self.tasks, &removed);
+        let mut position = 0;
+        self.tasks.retain(|_| {
+            position += 1;
+            removed.binary_search(&(position - 1)).is_err()
+        });
+        Ok(())
+    }
+    
+    /// Apply one batch operation, returning the ID of the task it changed
+    fn apply_operation(&mut self, operation: BatchOperation) -> Result<String, SchedulerError> {
+        match operation {
+            BatchOperation::Create { task } => {
+                let id = task.id.clone();
+                self.insert_task(*task)?;
+                Ok(id)
+            }
+            BatchOperation::Update { id, updates } => {
+                self.patch_task(&id, &updates)?;
+                Ok(id)
+            }
+            BatchOperation::Delete { id } => {
+                self.remove_task(&id)?;
+                Ok(id)
+            }
+            BatchOperation::Move { id, parent_id, sort_key } => {
+                let mut updates = serde_json::Map::new();
+                updates.insert("parentId".to_string(), parent_id.into());
+                if let Some(sort_key) = sort_key {
+                    updates.insert("sortKey".to_string(), sort_key.into());
+                }
+                self.patch_task(&id, &serde_json::Value::Object(updates))?;
+                Ok(id)
+            }
+        }
+    }
+    
+    /// Run the CPM calculation (see `calculate`)
+    fn run_calculation(&mut self, data_date: Option<String>) -> Result<CPMResult, SchedulerError> {
+        if !self.initialized {
+            return Err(SchedulerError::not_initialized());
+        }
+        
+        if let Some(date) = validate_data_date(data_date)? {
+            if self.data_date.as_deref() != Some(date.as_str()) {
+                self.invalidate();
+            }
+            self.data_date = Some(date);
+        }
+    
+        let calendar = self.calendar.as_ref()
+            .ok_or_else(|| SchedulerError::new(ErrorCode::NotInitialized, "Calendar not initialized"))?;
+        if self.day_indexes.is_none() {
+            self.day_indexes = DayIndexes::build(calendar, &self.calendars, &self.tasks, self.data_date.as_deref());
+        }
+        let calendars = Calendars::new(calendar, &self.calendars, self.day_indexes.as_ref(), &self.options);
+        
+        let tasks = &self.tasks;
+        let network = match self.network.take() {
+            Some(mut network) => {
+                if self.network_stale {
+                    network.rebuild(tasks, &self.task_index);
+                }
+                network
+            }
+            None => cpm::Network::build(tasks, &self.task_index),
+        };
+        let network = self.network.insert(network);
+        self.network_stale = false;
+        let dirty: Option<Vec<usize>> = (!self.recalc_all).then(|| {
+            self.dirty.iter()
+                .filter_map(|id| network.graph.index.get(id).copied())
+                .collect()
+        });
+        
+        // Run CPM calculation
+        let result = cpm::calculate(&mut self.tasks, &calendars, &self.options, self.data_date.as_deref(), self.opened_on.as_deref(), network, &self.task_index, dirty.as_deref());
+        self.dirty.clear();
+        self.recalc_all = false;
+        
+        log(&format!(
+            "[WASM] CPM complete: {} tasks, {} critical, {:.2}ms",
+            result.stats.task_count,
+            result.stats.critical_count,
+            result.stats.calc_time
+        ));
+        
+        Ok(result)
+    }
+    
+    /// Drop the cached network and recalculate everything next time
+    fn invalidate(&mut self) {
+        self.network = None;
+        self.network_stale = false;
+        self.dirty.clear();
+        self.recalc_all = true;
+    }
+    
+    /// Mark dirty everything a change to a task's links or hierarchy can
+    /// reach - called before and after the change
+    ///
+    /// A summary's start floor and finish ceiling reach every task below it,
+    /// and its rollup every summary above it (gaining or losing a child also
+    /// turns a parent into a summary or back). Each of those tasks is marked
+    /// with its predecessors and successors.
+    fn mark_structure_dirty(&mut self, index: usize) {
+        let ancestors: Vec<usize> = self.task_index.ancestors(&self.tasks, index).collect();
+        for i in self.subtree(index).into_iter().chain(ancestors) {
+            self.mark_links_dirty(i);
+        }
+    }
+    
+    /// Positions of a task and every task below it
+    fn subtree(&self, index: usize) -> Vec<usize> {
+        let mut subtree = vec![index];
+        let mut seen: HashSet<usize> = HashSet::from([index]);
+        let mut next = 0;
+        while let Some(&i) = subtree.get(next) {
+            next += 1;
+            // Bounded by `seen`, in case the hierarchy is already circular
+            let children = self.task_index.children(&self.tasks[i].id);
+            subtree.extend(children.iter().filter(|&&c| seen.insert(c)));
+        }
+        subtree
+    }
+    
+    /// Why `parent_id` cannot be the parent of `task_id`, if it cannot
+    ///
+    /// The same parents a load would reject (see `hierarchy.rs`). `action`
+    /// ("added", "moved") words the message.
+    fn parent_error(&self, task_id: &str, parent_id: &str, action: &str) -> Option<SchedulerError> {
+        let (code, problem) = if self.is_descendant_or_self(parent_id, task_id) {
+            (ErrorCode::CycleDetected, ": the hierarchy would be circular")
+        } else {
+            match self.task_index.get(parent_id) {
+                None => (ErrorCode::InvalidTask, ", which does not exist"),
+                Some(p) if self.tasks[p].row_type == Some(RowType::Blank) => (ErrorCode::InvalidTask, ", which is a blank row"),
+                Some(_) => return None,
+            }
+        };
+        Some(SchedulerError::new(code, format!("Task '{}' cannot be {} under '{}'{}", task_id, action, parent_id, problem))
+            .with_task(task_id)
+            .with_field("parentId"))
+    }
+    
+    /// Check the parent of a task already in the engine (see `parent_error`)
+    fn check_parent(&self, task_id: &str, action: &str) -> Result<(), SchedulerError> {
+        let parent_id = self.task_index.get(task_id).and_then(|i| self.tasks[i].parent_id.as_deref());
+        match parent_id.and_then(|parent_id| self.parent_error(task_id, parent_id, action)) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+    
+    /// Check whether `id` is `ancestor_id` or sits somewhere below it
+    fn is_descendant_or_self(&self, id: &str, ancestor_id: &str) -> bool {
+        let mut current = Some(id);
+        // Bounded walk, in case the hierarchy is already circular
+        for _ in 0..=self.tasks.len() {
+            match current {
+                Some(id) if id == ancestor_id => return true,
+                Some(id) => {
+                    current = self.task_index.get(id)
+                        .and_then(|i| self.tasks[i].parent_id.as_deref());
+                }
+                None => return false,
+            }
+        }
+        false
+    }
+    
+    /// Mark a task and its direct predecessors and successors dirty
+    fn mark_links_dirty(&mut self, index: usize) {
+        let task = &self.tasks[index];
+        self.dirty.insert(task.id.clone());
+        self.dirty.extend(task.dependencies.iter().map(|d| d.id.clone()));
+        let successors = self.task_index.successors(&task.id);
+        self.dirty.extend(successors.iter().map(|&s| self.tasks[s].id.clone()));
+    }
+}
+
+/// Check that a data date is a valid "YYYY-MM-DD" date, or "YYYY-MM-DDTHH:MM"
+/// when scheduling in hours (empty counts as unset)
+fn validate_data_date(data_date: Option<String>) -> Result<Option<String>, SchedulerError> {
+    match data_date {
+        Some(date) if !date.is_empty() => {
+            if date_utils::parse_moment(&date).is_none() {
+                return Err(SchedulerError::new(ErrorCode::InvalidDate, format!("Invalid data date '{}'", date)).with_field("dataDate"));
+            }
+            Ok(Some(date))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Deserialize a task list, naming the task a bad value belongs to
+fn deserialize_tasks(tasks_val: JsValue) -> Result<Vec<Task>, SchedulerError> {
+    serde_wasm_bindgen::from_value(tasks_val.clone()).or_else(|e| {
+        // Only a failed list is retried through JSON, one task at a time
+        let values: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(tasks_val)
+            .map_err(|_| SchedulerError::new(ErrorCode::InvalidTask, format!("Failed to deserialize tasks: {}", e)))?;
+        values.into_iter().map(task_from_value).collect()
+    })
+}
+
+/// Deserialize one task from JSON, naming it in the error
+fn task_from_value(value: serde_json::Value) -> Result<Task, SchedulerError> {
+    let id = value.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    serde_json::from_value(value).map_err(|e| {
+        SchedulerError::new(ErrorCode::InvalidTask, format!("Failed to deserialize task '{}': {}", id, e)).with_task(id)
+    })
+}
+
+/// Read one batch operation, naming it by position in the error
+fn parse_operation(index: usize, value: serde_json::Value) -> Result<BatchOperation, SchedulerError> {
+    // Task a malformed operation is about, if it says
+    let id = value.get("id")
+        .or_else(|| value.get("task").and_then(|t| t.get("id")))
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    serde_json::from_value(value).map_err(|e| {
+        let error = SchedulerError::new(ErrorCode::InvalidOperation, format!("Operation {}: {}", index, e));
+        match id {
+            Some(id) => error.with_task(id),
+            None => error,
+        }
+    })
+}
+
+/// Error for a rejected task update, with one detail per field
+fn invalid_patch(task_id: &str, errors: Vec<patch::FieldError>) -> SchedulerError {
+    let message = errors.iter()
+        .map(|e| format!("{}: {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let mut error = SchedulerError::new(ErrorCode::InvalidField, format!("Invalid update for task '{}': {}", task_id, message))
+        .with_task(task_id);
+    if let [only] = errors.as_slice() {
+        error = error.with_field(&only.field);
+    }
+    error.details = errors.into_iter()
+        .map(|e| {
+            SchedulerError::new(ErrorCode::InvalidField, e.message)
+                .with_task(task_id)
+                .with_field(e.field)
+        })
+        .collect();
+    error
+}
+
+/// Check the hierarchy of tasks being loaded, rejecting or repairing it
+/// according to `policy`
+fn load_hierarchy(tasks: &mut [Task], policy: HierarchyPolicy) -> Result<Vec<HierarchyIssue>, SchedulerError> {
+    let index = TaskIndex::build(tasks);
+    let mut issues = hierarchy::check_hierarchy(tasks, &index);
+    if issues.is_empty() {
+        return Ok(issues);
+    }
+    
+    match policy {
+        HierarchyPolicy::Reject => Err(invalid_hierarchy(&issues)),
+        HierarchyPolicy::Repair => {
+            hierarchy::repair_hierarchy(tasks, &index, &mut issues);
+            for issue in &issues {
+                warn(&format!("[WASM] {}", issue.message));
+            }
+            Ok(issues)
+        }
+    }
+}
+
+/// Error for a rejected hierarchy, with one detail per problem
+fn invalid_hierarchy(issues: &[HierarchyIssue]) -> SchedulerError {
+    let mut message = format!("Invalid task hierarchy: {}", issues[0].message);
+    if issues.len() > 1 {
+        message.push_str(&format!(" (and {} more)", issues.len() - 1));
+    }
+    let mut error = SchedulerError::new(ErrorCode::InvalidHierarchy, message);
+    error.details = issues.iter()
+        .map(|issue| {
+            let code = match issue.kind {
+                HierarchyIssueKind::SelfParent | HierarchyIssueKind::ParentCycle => ErrorCode::CycleDetected,
+                HierarchyIssueKind::DanglingParent | HierarchyIssueKind::BlankParent => ErrorCode::InvalidHierarchy,
+            };
+            SchedulerError::new(code, issue.message.clone())
+                .with_task(&issue.task_id)
+                .with_field("parentId")
+        })
+        .collect();
+    error
+}
+
+/// Check a calendar can be scheduled on (see `date_utils::validate_calendar`)
+fn validate_calendar(calendar: &Calendar, id: Option<&str>) -> Result<(), SchedulerError> {
+    date_utils::validate_calendar(calendar).map_err(|e| match id {
+        Some(id) => SchedulerError::new(ErrorCode::InvalidCalendar, format!("Invalid calendar '{}': {}", id, e)),
+        None => SchedulerError::new(ErrorCode::InvalidCalendar, format!("Invalid calendar: {}", e)),
+    })
+}
+
 impl Default for SchedulerEngine {
     fn default() -> Self {
         Self::new()
@@ -322,3 +907,296 @@ pub fn main() {
     utils::set_panic_hook();
     log("[WASM] Scheduler WASM module loaded");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{after, child, dep, task};
+    use crate::types::{Dependency, HealthReason, HealthStatus, LinkType};
+    use serde_json::json;
+    
+    /// Engine loaded with `tasks` on a Monday-to-Friday calendar
+    fn engine(tasks: Vec<Task>, data_date: &str) -> SchedulerEngine {
+        let mut engine = SchedulerEngine::new();
+        engine.task_index = TaskIndex::build(&tasks);
+        engine.tasks = tasks;
+        engine.calendar = Some(Calendar { working_days: vec![1, 2, 3, 4, 5], ..Calendar::default() });
+        engine.data_date = Some(data_date.to_string());
+        engine.initialized = true;
+        engine
+    }
+    
+    /// Small deterministic random number generator (xorshift)
+    struct Rng(u64);
+    
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+        
+        fn chance(&mut self, percent: usize) -> bool {
+            self.below(100) < percent
+        }
+        
+        fn pick(&mut self, tasks: &[Task]) -> String {
+            tasks[self.below(tasks.len())].id.clone()
+        }
+    }
+    
+    /// Everything a calculation writes to a task
+    fn calculated_fields(task: &Task) -> String {
+        let links: Vec<(String, i32, bool)> = task.predecessor_links.iter().flatten()
+            .map(|l| (l.predecessor_id.clone(), l.relative_float, l.driving))
+            .collect();
+        format!(
+            "{} {}-{} dur {} late {:?}-{:?} tf {:?} ff {:?} crit {:?}/{:?} dv {:?} by {:?} cyc {:?} health {:?} links {:?}",
+            task.id, task.start, task.end, task.duration, task.late_start, task.late_finish,
+            task.total_float_days, task.free_float_days, task.is_critical, task.is_near_critical,
+            task.deadline_variance, task.driven_by, task.in_cycle, task.health.as_ref().map(|h| h.status), links,
+        )
+    }
+    
+    /// The tasks recalculated from scratch (on the same working-day tables)
+    fn full_recalculation(engine: &mut SchedulerEngine) -> Vec<Task> {
+        let mut fresh = self::engine(engine.tasks.clone(), engine.data_date.as_deref().unwrap_or_default());
+        fresh.day_indexes = engine.day_indexes.take();
+        fresh.run_calculation(None).expect("full calculation");
+        engine.day_indexes = fresh.day_indexes.take();
+        fresh.tasks
+    }
+    
+    fn random_project(rng: &mut Rng, size: usize) -> Vec<Task> {
+        let mut tasks: Vec<Task> = Vec::new();
+        for i in 0..size {
+            let id = format!("T{}", i);
+            let mut task = match tasks.len() {
+                n if n > 0 && rng.chance(40) => child(&id, &tasks[rng.below(n)].id.clone(), rng.below(6) as i32),
+                _ => task(&id, rng.below(6) as i32),
+            };
+            for _ in 0..rng.below(3) {
+                if i > 0 {
+                    let pred = rng.pick(&tasks);
+                    task.dependencies.push(random_link(rng, &pred));
+                }
+            }
+            if rng.chance(15) {
+                task.constraint_type = [ConstraintType::Snet, ConstraintType::Fnlt, ConstraintType::Alap][rng.below(3)];
+                task.constraint_date = Some(format!("2024-01-{:02}", 10 + rng.below(15)));
+            }
+            tasks.push(task);
+        }
+        tasks
+    }
+    
+    fn random_link(rng: &mut Rng, pred: &str) -> Dependency {
+        let link_type = [LinkType::Fs, LinkType::Fs, LinkType::Ss, LinkType::Ff, LinkType::Sf][rng.below(5)];
+        dep(pred, link_type, rng.below(5) as i32 - 1)
+    }
+    
+    /// Make one random edit, returning a description of it
+    fn random_edit(rng: &mut Rng, engine: &mut SchedulerEngine, next_id: &mut usize) -> String {
+        let tasks = engine.tasks.clone();
+        if tasks.is_empty() {
+            return "nothing to edit".to_string();
+        }
+        let id = rng.pick(&tasks);
+        let (edit, result) = match rng.below(8) {
+            0 => {
+                let new_id = format!("N{}", *next_id);
+                *next_id += 1;
+                let mut new_task = match rng.chance(50) {
+                    true => child(&new_id, &id, 1 + rng.below(4) as i32),
+                    false => task(&new_id, 1 + rng.below(4) as i32),
+                };
+                if rng.chance(60) {
+                    let pred = rng.pick(&tasks);
+                    new_task.dependencies.push(random_link(rng, &pred));
+                }
+                (format!("insert {} under {:?}", new_id, new_task.parent_id), engine.insert_task(new_task))
+            }
+            1 => {
+                let parent = if rng.chance(25) { json!(null) } else { json!(rng.pick(&tasks)) };
+                (format!("move {} under {}", id, parent), engine.patch_task(&id, &json!({ "parentId": parent })))
+            }
+            2 => (format!("delete {}", id), engine.remove_task(&id)),
+            3 => (format!("finish {}", id), engine.patch_task(&id, &json!({ "actualStart": "2024-01-08", "actualFinish": "2024-01-09" }))),
+            4 => (format!("start {}", id), engine.patch_task(&id, &json!({ "actualStart": "2024-01-09" }))),
+            5 => {
+                let duration = rng.below(8);
+                (format!("duration of {} to {}", id, duration), engine.patch_task(&id, &json!({ "duration": duration })))
+            }
+            6 => {
+                let links = match rng.below(3) {
+                    0 => json!([]),
+                    _ => {
+                        let pred = rng.pick(&tasks);
+                        serde_json::to_value(vec![random_link(rng, &pred)]).unwrap()
+                    }
+                };
+                (format!("links of {} to {}", id, links), engine.patch_task(&id, &json!({ "dependencies": links })))
+            }
+            _ => {
+                let update = match rng.below(3) {
+                    0 => json!({ "constraintType": "asap", "constraintDate": null }),
+                    1 => json!({ "constraintType": "snet", "constraintDate": format!("2024-01-{:02}", 10 + rng.below(15)) }),
+                    _ => json!({ "constraintType": "fnlt", "constraintDate": format!("2024-01-{:02}", 10 + rng.below(15)) }),
+                };
+                (format!("constraint of {} to {}", id, update), engine.patch_task(&id, &update))
+            }
+        };
+        match result {
+            Ok(()) => edit,
+            Err(e) => format!("{} (rejected: {})", edit, e.message),
+        }
+    }
+    
+    #[test]
+    fn incremental_recalculation_matches_a_full_one() {
+        let mut failures = Vec::new();
+        // Every project covers the same few weeks, so one set of tables serves all
+        let mut day_indexes = None;
+        
+        for seed in 1..=300u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let mut engine = engine(random_project(&mut rng, 14), "2024-01-08");
+            engine.day_indexes = day_indexes.take();
+            engine.run_calculation(None).expect("first calculation");
+            let mut next_id = 0;
+            let mut edits = Vec::new();
+            
+            for _ in 0..6 {
+                let before: HashMap<String, String> = engine.tasks.iter()
+                    .map(|t| (t.id.clone(), calculated_fields(t)))
+                    .collect();
+                edits.push(random_edit(&mut rng, &mut engine, &mut next_id));
+                let result = engine.run_calculation(None).expect("incremental calculation");
+                
+                let expected: Vec<String> = full_recalculation(&mut engine).iter().map(calculated_fields).collect();
+                let actual: Vec<String> = engine.tasks.iter().map(calculated_fields).collect();
+                if let Some((a, e)) = actual.iter().zip(&expected).find(|(a, e)| a != e) {
+                    failures.push(format!("seed {}: {}\n  incremental: {}\n  full:        {}", seed, edits.join(", "), a, e));
+                    break;
+                }
+                
+                // Every task whose values moved is in the result
+                let returned: HashSet<&str> = result.tasks.iter().map(|t| t.id.as_str()).collect();
+                let missing: Vec<&str> = engine.tasks.iter()
+                    .filter(|t| before.get(&t.id) != Some(&calculated_fields(t)) && !returned.contains(t.id.as_str()))
+                    .map(|t| t.id.as_str())
+                    .collect();
+                if !result.full && !missing.is_empty() {
+                    failures.push(format!("seed {}: {}\n  changed but not returned: {:?}", seed, edits.join(", "), missing));
+                    break;
+                }
+            }
+            day_indexes = engine.day_indexes.take();
+        }
+        
+        assert!(failures.is_empty(), "{} of 300 edit sequences differ:\n{}", failures.len(), failures.join("\n"));
+    }
+    
+    #[test]
+    fn zero_float_on_the_critical_path_is_not_low_float() {
+        let tasks = vec![task("A", 3), task("B", 2), after(task("C", 1), &["A", "B"])];
+        let mut engine = engine(tasks, "2024-01-08");
+        engine.run_calculation(None).expect("calculation");
+        
+        let health = |id: &str| {
+            let task = &engine.tasks[engine.task_index.get(id).unwrap()];
+            (task.total_float_days, task.health.as_ref().map(|h| (h.status, h.reasons.clone())))
+        };
+        assert_eq!(health("A"), (Some(0), Some((HealthStatus::Healthy, vec![]))));
+        assert_eq!(health("B"), (Some(1), Some((HealthStatus::AtRisk, vec![HealthReason::LowFloat]))));
+    }
+    
+    #[test]
+    fn dcma_relationship_types_and_critical_path_test() {
+        let mut ss = task("C", 2);
+        ss.dependencies.push(dep("A", LinkType::Ss, 0));
+        let mut engine = engine(vec![task("A", 3), after(task("B", 2), &["A"]), ss], "2024-01-08");
+        engine.run_calculation(None).expect("calculation");
+        let assess = |engine: &SchedulerEngine| {
+            let calendar = engine.calendar.as_ref().unwrap();
+            let calendars = Calendars::new(calendar, &engine.calendars, engine.day_indexes.as_ref(), &engine.options);
+            dcma::assess(&engine.tasks, &engine.task_index, &calendars, &engine.options, engine.data_date.as_deref(), None)
+        };
+        
+        let checks = assess(&engine);
+        let types = &checks[3];
+        assert_eq!((types.passed, types.value, types.threshold), (false, 50.0, 90.0));
+        assert_eq!(types.task_ids, vec!["C"]);
+        assert_eq!(types.message, "1 of 2 links are finish-to-start (50.0%, minimum 90%)");
+        
+        let cpt = &checks[11];
+        assert_eq!((cpt.passed, cpt.value, cpt.threshold), (true, 20.0, 20.0));
+        assert_eq!(cpt.message, "Delaying 'A' by 20 moved its finish by 20 and the project finish by 20");
+        
+        // Nothing left to delay once every critical activity has started
+        for t in &mut engine.tasks {
+            t.actual_start = Some("2024-01-08".to_string());
+        }
+        engine.run_calculation(None).expect("calculation");
+        let cpt = &assess(&engine)[11];
+        assert_eq!((cpt.passed, cpt.value, cpt.threshold), (true, 0.0, 20.0));
+        assert!(cpt.message.starts_with("Not applicable"), "{}", cpt.message);
+    }
+    
+    #[test]
+    fn a_patch_cannot_move_a_task_under_a_missing_parent_or_a_blank_row() {
+        let blank = Task { row_type: Some(RowType::Blank), ..task("B", 0) };
+        let mut engine = engine(vec![task("A", 1), blank, task("C", 1)], "2024-01-08");
+        
+        for parent in ["Missing", "B"] {
+            let error = engine.patch_task("C", &json!({ "parentId": parent })).unwrap_err();
+            assert_eq!(error.code, ErrorCode::InvalidTask, "{}", error.message);
+            assert_eq!(error.field.as_deref(), Some("parentId"));
+            assert_eq!(engine.tasks[2].parent_id, None);
+        }
+        engine.patch_task("C", &json!({ "parentId": "A" })).expect("valid parent");
+        assert_eq!(engine.tasks[2].parent_id.as_deref(), Some("A"));
+    }
+    
+    #[test]
+    fn an_added_task_needs_an_existing_non_blank_parent_other_than_itself() {
+        let blank = Task { row_type: Some(RowType::Blank), ..task("B", 0) };
+        let mut engine = engine(vec![task("A", 1), blank], "2024-01-08");
+        
+        for (parent, code) in [("Missing", ErrorCode::InvalidTask), ("B", ErrorCode::InvalidTask), ("C", ErrorCode::CycleDetected)] {
+            let error = engine.create_task(child("C", parent, 1)).unwrap_err();
+            assert_eq!(error.code, code, "{}", error.message);
+            assert_eq!(error.field.as_deref(), Some("parentId"));
+            assert_eq!(engine.tasks.len(), 2);
+        }
+        engine.create_task(child("C", "A", 1)).expect("valid parent");
+        assert_eq!(engine.tasks[2].parent_id.as_deref(), Some("A"));
+    }
+    
+    #[test]
+    fn a_batch_can_create_children_before_their_parent() {
+        let mut engine = engine(vec![task("A", 1)], "2024-01-08");
+        let create = |task: Task| BatchOperation::Create { task: Box::new(task) };
+        
+        let changes = engine.apply_operations(vec![create(child("C1", "P", 1)), create(child("C2", "P", 1)), create(task("P", 0))])
+            .expect("parent is in the batch");
+        assert_eq!(changes.created, ["C1", "C2", "P"]);
+        assert_eq!(engine.task_index.get("C1").map(|i| engine.tasks[i].parent_id.as_deref()), Some(Some("P")));
+    }
+    
+    #[test]
+    fn a_batch_creating_a_task_under_a_bad_parent_is_rolled_back() {
+        let blank = Task { row_type: Some(RowType::Blank), ..task("B", 0) };
+        let mut engine = engine(vec![task("A", 1), blank], "2024-01-08");
+        let create = |task: Task| BatchOperation::Create { task: Box::new(task) };
+        
+        for parent in ["Missing", "B", "C"] {
+            let operations = vec![create(task("D", 1)), create(child("C", parent, 1))];
+            let error = engine.apply_operations(operations).unwrap_err();
+            assert!(error.message.starts_with("Operation 1 (create): "), "{}", error.message);
+            assert_eq!(engine.tasks.len(), 2);
+            assert!(engine.task_index.get("D").is_none());
+        }
+    }
+}
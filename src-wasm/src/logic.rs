@@ -0,0 +1,150 @@
+//! Dependency link validation
+//!
+//! The CPM passes skip links they cannot use (to missing tasks or blank
+//! rows) without a word. `validate_logic` reports those along with links
+//! that schedule but are likely mistakes:
+//! - a task linked to itself, or to the same predecessor twice
+//! - a link between a task and one of its own summaries
+//! - a lag (or lead) longer than the task holding the link, unless that
+//!   task is a milestone (waiting on a predecessor is what a milestone is for)
+
+use crate::task_index::TaskIndex;
+use crate::types::{Dependency, LogicIssue, LogicIssueKind, RowType, Task};
+use std::collections::HashSet;
+
+/// Find every problem with the tasks' links, in task and link order
+pub fn validate_logic(tasks: &[Task], index: &TaskIndex) -> Vec<LogicIssue> {
+    let mut issues = Vec::new();
+
+    for (i, task) in tasks.iter().enumerate() {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for dep in &task.dependencies {
+            let mut report = |kind, message| issues.push(LogicIssue {
+                kind,
+                task_id: task.id.clone(),
+                predecessor_id: dep.id.clone(),
+                link_type: dep.link_type,
+                lag: dep.lag,
+                message,
+            });
+
+            if !seen.insert(dep.id.as_str()) {
+                report(LogicIssueKind::DuplicateLink, format!("Task '{}' links to '{}' more than once", task.id, dep.id));
+                continue;
+            }
+            if dep.id == task.id {
+                report(LogicIssueKind::SelfLink, format!("Task '{}' links to itself", task.id));
+                continue;
+            }
+            let Some(pred) = index.get(&dep.id) else {
+                report(LogicIssueKind::DanglingPredecessor, format!("Task '{}' links to '{}', which does not exist", task.id, dep.id));
+                continue;
+            };
+
+            if task.row_type == Some(RowType::Blank) {
+                report(LogicIssueKind::BlankRowLink, format!("Blank row '{}' has a link from '{}'", task.id, dep.id));
+            } else if tasks[pred].row_type == Some(RowType::Blank) {
+                report(LogicIssueKind::BlankRowLink, format!("Task '{}' links to blank row '{}'", task.id, dep.id));
+            }
+
+            if index.ancestors(tasks, i).any(|a| a == pred) {
+                report(LogicIssueKind::AncestorLink, format!("Task '{}' links to its own summary '{}'", task.id, dep.id));
+            } else if index.ancestors(tasks, pred).any(|a| a == i) {
+                report(LogicIssueKind::AncestorLink, format!("Summary '{}' links to '{}', which is below it", task.id, dep.id));
+            }
+
+            if exceeds_duration(dep, task) {
+                report(LogicIssueKind::LagExceedsDuration, format!(
+                    "Lag of {} on the link from '{}' is longer than '{}' itself ({})",
+                    dep.lag, dep.id, task.id, task.duration,
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Whether a link's lag or lead is longer than the task holding it, which
+/// is not a milestone
+fn exceeds_duration(dep: &Dependency, task: &Task) -> bool {
+    task.duration != 0 && dep.lag.unsigned_abs() > task.duration.unsigned_abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{after, child, dep, task};
+    use crate::types::LinkType;
+
+    fn blank(id: &str) -> Task {
+        Task { row_type: Some(RowType::Blank), ..task(id, 0) }
+    }
+
+    /// The kind and predecessor of every issue found
+    fn found(tasks: Vec<Task>) -> Vec<(LogicIssueKind, String, String)> {
+        let index = TaskIndex::build(&tasks);
+        validate_logic(&tasks, &index).into_iter()
+            .map(|issue| (issue.kind, issue.task_id, issue.predecessor_id))
+            .collect()
+    }
+
+    fn issue(kind: LogicIssueKind, task_id: &str, predecessor_id: &str) -> (LogicIssueKind, String, String) {
+        (kind, task_id.to_string(), predecessor_id.to_string())
+    }
+
+    #[test]
+    fn sound_links_have_no_issues() {
+        assert!(found(vec![task("A", 2), after(task("B", 2), &["A"])]).is_empty());
+    }
+
+    #[test]
+    fn a_second_link_to_the_same_predecessor_is_a_duplicate() {
+        let mut b = after(task("B", 2), &["A"]);
+        b.dependencies.push(dep("A", LinkType::Ss, 1));
+        assert_eq!(found(vec![task("A", 2), b]), [issue(LogicIssueKind::DuplicateLink, "B", "A")]);
+    }
+
+    #[test]
+    fn a_task_cannot_link_to_itself() {
+        assert_eq!(found(vec![after(task("A", 2), &["A"])]), [issue(LogicIssueKind::SelfLink, "A", "A")]);
+    }
+
+    #[test]
+    fn a_link_to_a_missing_task_dangles() {
+        let issues = found(vec![after(task("A", 2), &["Gone"])]);
+        assert_eq!(issues, [issue(LogicIssueKind::DanglingPredecessor, "A", "Gone")]);
+    }
+
+    #[test]
+    fn links_to_and_from_blank_rows_are_reported() {
+        let tasks = vec![task("A", 2), after(blank("B"), &["A"]), after(task("C", 2), &["B"])];
+        assert_eq!(found(tasks), [
+            issue(LogicIssueKind::BlankRowLink, "B", "A"),
+            issue(LogicIssueKind::BlankRowLink, "C", "B"),
+        ]);
+    }
+
+    #[test]
+    fn links_between_a_task_and_its_summaries_are_reported() {
+        let tasks = vec![after(task("P", 0), &["C"]), child("S", "P", 0), after(child("C", "S", 2), &["P"])];
+        assert_eq!(found(tasks), [
+            issue(LogicIssueKind::AncestorLink, "P", "C"),
+            issue(LogicIssueKind::AncestorLink, "C", "P"),
+        ]);
+    }
+
+    #[test]
+    fn lags_and_leads_longer_than_the_task_are_reported_except_into_milestones() {
+        let lagged = |id: &str, duration, lag| {
+            let mut t = task(id, duration);
+            t.dependencies.push(dep("A", LinkType::Fs, lag));
+            t
+        };
+        let tasks = vec![task("A", 2), lagged("B", 2, 2), lagged("C", 2, 3), lagged("D", 2, -3), lagged("M", 0, 5)];
+        assert_eq!(found(tasks), [
+            issue(LogicIssueKind::LagExceedsDuration, "C", "A"),
+            issue(LogicIssueKind::LagExceedsDuration, "D", "A"),
+        ]);
+    }
+}
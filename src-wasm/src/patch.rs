@@ -0,0 +1,233 @@
+//! Partial task updates
+//!
+//! A patch is a JS object holding any of a task's fields under their JS
+//! names. It is merged onto the task's own serialized form and read back as
+//! a `Task`, so every field `types::Task` has can be patched and the patch
+//! follows the same rules as loading a task:
+//! - an explicit `null` clears an optional field
+//! - keys the engine doesn't know (UI-only fields) are ignored
+//! - fields the calculation writes (float, late dates, ...) cannot be set
+//!
+//! A patch applies whole or not at all. Every field with the wrong type or
+//! an out-of-range value is reported, each with its own message.
+
+use crate::date_utils::parse_moment;
+use crate::types::Task;
+use serde_json::{Map, Value};
+
+/// Fields holding dates ("YYYY-MM-DD", or a moment when scheduling in hours)
+const DATE_FIELDS: &[&str] = &[
+    "start", "end", "constraintDate", "deadline", "actualStart", "actualFinish",
+    "baselineStart", "baselineFinish",
+];
+
+/// Fields only the calculation writes
+const CALCULATED_FIELDS: &[&str] = &[
+    "_isCritical", "_isNearCritical", "_totalFloat", "_freeFloat", "lateStart", "lateFinish",
+    "totalFloat", "freeFloat", "_deadlineVariance", "_drivenBy", "_predecessorLinks", "_inCycle", "_health",
+];
+
+/// A patch field that could not be applied
+#[derive(Clone, Debug)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Apply a patch object to a task
+///
+/// Returns the patched fields the task knows about, or every field that
+/// could not be applied (the task is then left unchanged).
+pub fn apply_patch(task: &mut Task, patch: &Value) -> Result<Vec<String>, Vec<FieldError>> {
+    let Some(patch) = patch.as_object() else {
+        return Err(vec![FieldError {
+            field: String::new(),
+            message: format!("expected an object of fields, got {}", patch),
+        }]);
+    };
+    let calculated: Vec<FieldError> = patch.keys()
+        .filter(|key| CALCULATED_FIELDS.contains(&key.as_str()))
+        .map(|key| FieldError { field: key.clone(), message: "is calculated by the schedule and cannot be set".to_string() })
+        .collect();
+    if !calculated.is_empty() {
+        return Err(calculated);
+    }
+    let original = task_fields(task);
+
+    // Unknown keys are left out, so only real task fields count as patched
+    let fields: Vec<(&String, &Value)> = patch.iter()
+        .filter(|(key, _)| original.contains_key(*key))
+        .collect();
+
+    let mut merged = original.clone();
+    for (key, value) in &fields {
+        merged.insert((*key).clone(), (*value).clone());
+    }
+
+    let patched = match serde_json::from_value::<Task>(Value::Object(merged)) {
+        Ok(patched) => patched,
+        // Find which fields are at fault by applying each one on its own
+        Err(merged_error) => {
+            let mut errors: Vec<FieldError> = fields.iter()
+                .filter_map(|(key, value)| {
+                    let mut single = original.clone();
+                    single.insert((*key).clone(), (*value).clone());
+                    let message = match serde_json::from_value::<Task>(Value::Object(single)) {
+                        Ok(single) => check_range(&single, task, key).err()?,
+                        Err(e) => e.to_string(),
+                    };
+                    Some(FieldError { field: (*key).clone(), message })
+                })
+                .collect();
+            if errors.is_empty() {
+                errors.push(FieldError { field: String::new(), message: merged_error.to_string() });
+            }
+            return Err(errors);
+        }
+    };
+
+    let errors: Vec<FieldError> = fields.iter()
+        .filter_map(|(key, _)| {
+            check_range(&patched, task, key).err()
+                .map(|message| FieldError { field: (*key).clone(), message })
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Not read back from the merged fields, so carried over as it was
+    *task = Task { health: task.health.take(), ..patched };
+    Ok(fields.into_iter().map(|(key, _)| key.clone()).collect())
+}
+
+/// A task's fields under their JS names
+fn task_fields(task: &Task) -> Map<String, Value> {
+    match serde_json::to_value(task) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    }
+}
+
+/// Check a patched field holds an acceptable value
+fn check_range(patched: &Task, original: &Task, field: &str) -> Result<(), String> {
+    let at_least_zero = |value: Option<i32>| match value {
+        Some(v) if v < 0 => Err(format!("must not be negative (got {})", v)),
+        _ => Ok(()),
+    };
+
+    match field {
+        "id" if patched.id != original.id => Err("a task's ID cannot be changed".to_string()),
+        "parentId" if patched.parent_id.as_deref() == Some(patched.id.as_str()) => {
+            Err("a task cannot be its own parent".to_string())
+        }
+        "duration" => at_least_zero(Some(patched.duration)),
+        "remainingDuration" => at_least_zero(patched.remaining_duration),
+        "baselineDuration" => at_least_zero(patched.baseline_duration),
+        "level" => at_least_zero(Some(patched.level)),
+        "progress" if !(0..=100).contains(&patched.progress) => {
+            Err(format!("must be between 0 and 100 (got {})", patched.progress))
+        }
+        _ if DATE_FIELDS.contains(&field) => check_date(patched, field),
+        _ => Ok(()),
+    }
+}
+
+/// Check a date field is empty, unset or a valid date / moment
+fn check_date(task: &Task, field: &str) -> Result<(), String> {
+    let value = match field {
+        "start" => Some(&task.start),
+        "end" => Some(&task.end),
+        "constraintDate" => task.constraint_date.as_ref(),
+        "deadline" => task.deadline.as_ref(),
+        "actualStart" => task.actual_start.as_ref(),
+        "actualFinish" => task.actual_finish.as_ref(),
+        "baselineStart" => task.baseline_start.as_ref(),
+        "baselineFinish" => task.baseline_finish.as_ref(),
+        _ => None,
+    };
+
+    match value {
+        Some(date) if !date.is_empty() && parse_moment(date).is_none() => {
+            Err(format!("invalid date '{}' (expected YYYY-MM-DD)", date))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::task;
+    use crate::types::{HealthIndicator, HealthReason, HealthStatus};
+    use serde_json::json;
+
+    /// Fields reported for a patch that must fail, with `task` left unchanged
+    fn rejected(patch: Value) -> Vec<String> {
+        let mut patched = task("A", 3);
+        let errors = apply_patch(&mut patched, &patch).expect_err("patch should fail");
+        assert_eq!(task_fields(&patched), task_fields(&task("A", 3)));
+        errors.into_iter().map(|e| e.field).collect()
+    }
+
+    #[test]
+    fn known_fields_are_applied_and_unknown_keys_ignored() {
+        let mut patched = task("A", 3);
+        let fields = apply_patch(&mut patched, &json!({ "duration": 5, "deadline": "2024-01-12", "_selected": true })).unwrap();
+        assert_eq!(fields, ["deadline", "duration"]);
+        assert_eq!(patched.duration, 5);
+        assert_eq!(patched.deadline.as_deref(), Some("2024-01-12"));
+    }
+
+    #[test]
+    fn null_clears_an_optional_field() {
+        let mut patched = Task { deadline: Some("2024-01-12".to_string()), ..task("A", 3) };
+        apply_patch(&mut patched, &json!({ "deadline": null })).unwrap();
+        assert_eq!(patched.deadline, None);
+    }
+
+    #[test]
+    fn every_field_of_the_wrong_type_is_reported() {
+        assert_eq!(rejected(json!({ "duration": "three", "constraintType": "soon", "name": "ok" })), ["constraintType", "duration"]);
+        assert_eq!(rejected(json!("duration")), [""]);
+    }
+
+    #[test]
+    fn out_of_range_values_are_reported() {
+        assert_eq!(rejected(json!({ "duration": -1 })), ["duration"]);
+        assert_eq!(rejected(json!({ "remainingDuration": -2, "progress": 101 })), ["progress", "remainingDuration"]);
+        assert_eq!(rejected(json!({ "id": "B" })), ["id"]);
+        assert_eq!(rejected(json!({ "parentId": "A" })), ["parentId"]);
+        assert_eq!(rejected(json!({ "constraintDate": "next week", "actualStart": "2024-02-30" })), ["actualStart", "constraintDate"]);
+    }
+
+    #[test]
+    fn empty_dates_and_moments_are_accepted() {
+        let mut patched = task("A", 3);
+        apply_patch(&mut patched, &json!({ "start": "", "actualStart": "2024-01-08T08:00", "deadline": "2024-01-12" })).unwrap();
+        assert_eq!(patched.actual_start.as_deref(), Some("2024-01-08T08:00"));
+    }
+
+    #[test]
+    fn calculated_fields_cannot_be_set() {
+        assert_eq!(rejected(json!({ "_isCritical": true, "lateStart": "2024-01-08", "totalFloat": 2 })), ["_isCritical", "lateStart", "totalFloat"]);
+        assert_eq!(rejected(json!({ "duration": 5, "_drivenBy": null })), ["_drivenBy"]);
+    }
+
+    #[test]
+    fn a_patch_keeps_the_task_health() {
+        let health = HealthIndicator {
+            status: HealthStatus::AtRisk,
+            reasons: vec![HealthReason::LowFloat],
+            icon: String::new(),
+            summary: "Low float".to_string(),
+            details: vec![],
+            constraint_variance: None,
+            constraint_target: None,
+            projected_date: None,
+        };
+        let mut patched = Task { health: Some(health.clone()), ..task("A", 3) };
+        apply_patch(&mut patched, &json!({ "name": "Renamed" })).unwrap();
+        assert_eq!(patched.health, Some(health));
+    }
+}
@@ -0,0 +1,259 @@
+//! ID and hierarchy lookups over the engine's task list
+//!
+//! The engine keeps a `TaskIndex` next to its tasks and updates it on every
+//! add, update and delete, so finding a task by ID or listing a parent's
+//! children or a task's successors never scans the list. The CPM rollups use it to visit parents
+//! deepest first without walking the hierarchy once per task.
+
+use crate::types::{Dependency, Task};
+use std::collections::HashMap;
+
+/// Task ID -> position, parent ID -> child positions, and predecessor ID ->
+/// successor positions
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct TaskIndex {
+    /// Position of each task ID (the first one, if an ID is repeated)
+    positions: HashMap<String, usize>,
+    /// Positions of each parent's children, in task order. Keyed by the
+    /// `parentId` the children name, whether or not that task exists.
+    children: HashMap<String, Vec<usize>>,
+    /// Positions of the tasks linked from each predecessor ID, in task order
+    successors: HashMap<String, Vec<usize>>,
+}
+
+impl TaskIndex {
+    pub fn build(tasks: &[Task]) -> TaskIndex {
+        let mut index = TaskIndex::default();
+        for (i, task) in tasks.iter().enumerate() {
+            index.positions.entry(task.id.clone()).or_insert(i);
+            if let Some(parent_id) = &task.parent_id {
+                index.children.entry(parent_id.clone()).or_default().push(i);
+            }
+            index.link(&task.dependencies, i);
+        }
+        index
+    }
+
+    /// Position of the task with this ID
+    pub fn get(&self, id: &str) -> Option<usize> {
+        self.positions.get(id).copied()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.positions.contains_key(id)
+    }
+
+    /// Positions of the tasks whose `parentId` is `parent_id`
+    pub fn children(&self, parent_id: &str) -> &[usize] {
+        self.children.get(parent_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Positions of the tasks with a link from `predecessor_id`
+    pub fn successors(&self, predecessor_id: &str) -> &[usize] {
+        self.successors.get(predecessor_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Position of a task's parent, if the parent is in the list
+    pub fn parent(&self, tasks: &[Task], i: usize) -> Option<usize> {
+        tasks[i].parent_id.as_deref().and_then(|pid| self.get(pid))
+    }
+
+    /// Positions of a task's ancestors, nearest first (cut short if the
+    /// hierarchy loops)
+    pub fn ancestors<'a>(&'a self, tasks: &'a [Task], i: usize) -> impl Iterator<Item = usize> + 'a {
+        std::iter::successors(self.parent(tasks, i), move |&p| self.parent(tasks, p))
+            .take(tasks.len())
+    }
+
+    /// Record a task just appended at `position`
+    pub fn push(&mut self, task: &Task, position: usize) {
+        self.positions.entry(task.id.clone()).or_insert(position);
+        if let Some(parent_id) = &task.parent_id {
+            self.children.entry(parent_id.clone()).or_default().push(position);
+        }
+        self.link(&task.dependencies, position);
+    }
+
+    /// Move the task at `position` from one parent to another
+    pub fn reparent(&mut self, position: usize, from: Option<&str>, to: Option<&str>) {
+        if from == to {
+            return;
+        }
+        if let Some(from) = from {
+            self.unlink_child(from, position);
+        }
+        if let Some(to) = to {
+            let children = self.children.entry(to.to_string()).or_default();
+            if let Err(slot) = children.binary_search(&position) {
+                children.insert(slot, position);
+            }
+        }
+    }
+
+    /// Replace the links of the task at `position`
+    pub fn relink(&mut self, position: usize, from: &[Dependency], to: &[Dependency]) {
+        self.unlink(from, position);
+        self.link(to, position);
+    }
+
+    /// Forget the tasks at `removed` (ascending positions), before they are
+    /// removed from the list
+    ///
+    /// The tasks left move up past every removed one before them, in a
+    /// single pass over the index.
+    pub fn remove(&mut self, tasks: &[Task], removed: &[usize]) {
+        for &position in removed {
+            let id = &tasks[position].id;
+            if self.positions.get(id) == Some(&position) {
+                self.positions.remove(id);
+            }
+        }
+
+        let shift = |p: usize| p - removed.partition_point(|&r| r < p);
+        for p in self.positions.values_mut() {
+            *p = shift(*p);
+        }
+        for lists in [&mut self.children, &mut self.successors] {
+            lists.retain(|_, list| {
+                list.retain(|p| removed.binary_search(p).is_err());
+                for p in list.iter_mut() {
+                    *p = shift(*p);
+                }
+                !list.is_empty()
+            });
+        }
+    }
+
+    fn unlink_child(&mut self, parent_id: &str, position: usize) {
+        if let Some(children) = self.children.get_mut(parent_id) {
+            children.retain(|&c| c != position);
+            if children.is_empty() {
+                self.children.remove(parent_id);
+            }
+        }
+    }
+
+    fn link(&mut self, dependencies: &[Dependency], position: usize) {
+        for dep in dependencies {
+            let successors = self.successors.entry(dep.id.clone()).or_default();
+            if let Err(slot) = successors.binary_search(&position) {
+                successors.insert(slot, position);
+            }
+        }
+    }
+
+    fn unlink(&mut self, dependencies: &[Dependency], position: usize) {
+        for dep in dependencies {
+            if let Some(successors) = self.successors.get_mut(&dep.id) {
+                successors.retain(|&s| s != position);
+                if successors.is_empty() {
+                    self.successors.remove(&dep.id);
+                }
+            }
+        }
+    }
+
+    /// Depth of every task in the hierarchy (0 = top level)
+    ///
+    /// Each task is visited once. A circular hierarchy is cut where the walk
+    /// comes back to a task it has already passed.
+    pub fn depths(&self, tasks: &[Task]) -> Vec<usize> {
+        let mut depths: Vec<Option<usize>> = vec![None; tasks.len()];
+        let mut on_chain = vec![false; tasks.len()];
+
+        for start in 0..tasks.len() {
+            // Walk up to the first ancestor whose depth is known
+            let mut chain: Vec<usize> = Vec::new();
+            let mut known = None;
+            let mut next = Some(start);
+            while let Some(i) = next {
+                if let Some(depth) = depths[i] {
+                    known = Some(depth);
+                    break;
+                }
+                if on_chain[i] {
+                    break;
+                }
+                on_chain[i] = true;
+                chain.push(i);
+                next = self.parent(tasks, i);
+            }
+
+            let top = known.map_or(0, |d| d + 1);
+            for (depth, &i) in (top..).zip(chain.iter().rev()) {
+                depths[i] = Some(depth);
+                on_chain[i] = false;
+            }
+        }
+
+        depths.into_iter().map(|d| d.unwrap_or(0)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{after, child, task};
+
+    /// Every kind of entry: roots, nested children, links across the
+    /// hierarchy and a link to a missing task
+    fn project() -> Vec<Task> {
+        vec![
+            task("P", 0),
+            child("A", "P", 1),
+            after(child("B", "P", 1), &["A"]),
+            child("Q", "P", 0),
+            after(child("C", "Q", 1), &["A", "B"]),
+            after(task("D", 1), &["C", "Gone"]),
+            after(task("E", 1), &["A", "D"]),
+        ]
+    }
+
+    #[test]
+    fn pushing_tasks_matches_a_build() {
+        let tasks = project();
+        let mut index = TaskIndex::default();
+        for (i, task) in tasks.iter().enumerate() {
+            index.push(task, i);
+        }
+        assert_eq!(index, TaskIndex::build(&tasks));
+    }
+
+    #[test]
+    fn reparenting_and_relinking_match_a_build() {
+        let mut tasks = project();
+        let mut index = TaskIndex::build(&tasks);
+
+        // Move D under P, A under Q, then D back to the top level
+        for (i, to) in [(5, Some("P")), (1, Some("Q")), (5, None)] {
+            let from = tasks[i].parent_id.take();
+            index.reparent(i, from.as_deref(), to);
+            tasks[i].parent_id = to.map(str::to_string);
+            assert_eq!(index, TaskIndex::build(&tasks));
+        }
+
+        // Swap E's links for new ones, then drop B's
+        let relinked = after(task("E", 1), &["B", "Q"]).dependencies;
+        for (i, to) in [(6, relinked), (2, vec![])] {
+            let from = std::mem::replace(&mut tasks[i].dependencies, to);
+            index.relink(i, &from, &tasks[i].dependencies);
+            assert_eq!(index, TaskIndex::build(&tasks));
+        }
+    }
+
+    #[test]
+    fn removing_tasks_matches_a_build() {
+        // A leaf, a whole summary with its children, the first and last tasks
+        for removed in [vec![2], vec![3, 4], vec![0, 1, 2, 3, 4], vec![6], vec![0]] {
+            let mut tasks = project();
+            let mut index = TaskIndex::build(&tasks);
+            index.remove(&tasks, &removed);
+            let mut position = 0;
+            tasks.retain(|_| {
+                position += 1;
+                removed.binary_search(&(position - 1)).is_err()
+            });
+            assert_eq!(index, TaskIndex::build(&tasks), "removing {:?}", removed);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Task builders shared by the unit tests
+
+use crate::types::{Dependency, LinkType, Task};
+
+/// An automatically scheduled top-level task with no links
+pub fn task(id: &str, duration: i32) -> Task {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "name": id,
+        "parentId": null,
+        "sortKey": id,
+        "duration": duration,
+        "constraintType": "asap",
+        "constraintDate": null,
+        "dependencies": [],
+        "progress": 0,
+        "notes": "",
+    }))
+    .expect("valid task")
+}
+
+/// A task placed under `parent_id`
+pub fn child(id: &str, parent_id: &str, duration: i32) -> Task {
+    Task { parent_id: Some(parent_id.to_string()), ..task(id, duration) }
+}
+
+/// A link from the predecessor `id`
+pub fn dep(id: &str, link_type: LinkType, lag: i32) -> Dependency {
+    Dependency { id: id.to_string(), link_type, lag }
+}
+
+/// `task` with FS links from each of `predecessors`
+pub fn after(mut task: Task, predecessors: &[&str]) -> Task {
+    task.dependencies.extend(predecessors.iter().map(|p| dep(p, LinkType::Fs, 0)));
+    task
+}
@@ -3,7 +3,10 @@
 //! These types mirror the TypeScript interfaces in src/types/index.ts
 //! IMPORTANT: Field names use camelCase via serde rename to match JS
 
-use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
 
 /// Dependency link between tasks
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -12,11 +15,11 @@ pub struct Dependency {
     /// Predecessor task ID
     pub id: String,
     
-    /// Link type: "FS", "SS", "FF", "SF"
+    /// Link type
     #[serde(rename = "type")]
-    pub link_type: String,
+    pub link_type: LinkType,
     
-    /// Lag in working days (can be negative)
+    /// Lag in working days, or hours when scheduling in hours (can be negative)
     pub lag: i32,
 }
 
@@ -34,10 +37,9 @@ pub struct Task {
     #[serde(rename = "sortKey")]
     pub sort_key: String,
     
-    /// Row type: "task" (default), "blank" (spacer)
-    /// Blank rows are skipped by CPM calculations
+    /// Row type (None = task). Blank rows are skipped by CPM calculations
     #[serde(rename = "rowType", default)]
-    pub row_type: Option<String>,
+    pub row_type: Option<RowType>,
     
     /// Hierarchy level (0 = root). Calculated field - defaults to 0 if missing.
     #[serde(default)]
@@ -50,19 +52,26 @@ pub struct Task {
     /// End date. Calculated by CPM - defaults to empty string if missing.
     #[serde(default)]
     pub end: String,
+    /// Working days, or hours when scheduling in hours
     pub duration: i32,
     
     #[serde(rename = "constraintType")]
-    pub constraint_type: String,
+    pub constraint_type: ConstraintType,
     
     #[serde(rename = "constraintDate")]
     pub constraint_date: Option<String>,
     
-    /// Scheduling mode: "Auto" or "Manual"
-    /// Auto: CPM calculates dates from dependencies
-    /// Manual: User-fixed dates, CPM skips dependency calculation
-    #[serde(rename = "schedulingMode", default = "default_scheduling_mode")]
-    pub scheduling_mode: String,
+    /// Deadline date. Unlike a constraint it only limits the late finish:
+    /// missing it shows up as negative float, not as a moved bar.
+    #[serde(default)]
+    pub deadline: Option<String>,
+    
+    #[serde(rename = "schedulingMode", default)]
+    pub scheduling_mode: SchedulingMode,
+    
+    /// Named calendar the task works on (None = project calendar)
+    #[serde(rename = "calendarId", default)]
+    pub calendar_id: Option<String>,
     
     pub dependencies: Vec<Dependency>,
     
@@ -74,6 +83,9 @@ pub struct Task {
     #[serde(rename = "_isCritical", default)]
     pub is_critical: Option<bool>,
     
+    #[serde(rename = "_isNearCritical", default)]
+    pub is_near_critical: Option<bool>,
+    
     #[serde(rename = "_totalFloat", default)]
     pub total_float: Option<f64>,
     
@@ -92,6 +104,26 @@ pub struct Task {
     #[serde(rename = "freeFloat", default)]
     pub free_float_days: Option<i32>,
     
+    /// Work days between finish and deadline (negative = deadline missed)
+    #[serde(rename = "_deadlineVariance", default)]
+    pub deadline_variance: Option<i32>,
+    
+    /// What set the task's early start
+    #[serde(rename = "_drivenBy", default)]
+    pub driven_by: Option<StartDriver>,
+    
+    /// Incoming links with their relative float (which ones are driving)
+    #[serde(rename = "_predecessorLinks", default)]
+    pub predecessor_links: Option<Vec<LinkAnalysis>>,
+    
+    /// True if the task is part of a circular dependency (UI shows it as "Blocked")
+    #[serde(rename = "_inCycle", default)]
+    pub in_cycle: Option<bool>,
+    
+    /// Schedule health, recalculated by every calculation (see `health.rs`)
+    #[serde(rename = "_health", skip_deserializing)]
+    pub health: Option<HealthIndicator>,
+    
     // === UI State ===
     #[serde(rename = "_collapsed", default)]
     pub collapsed: Option<bool>,
@@ -126,6 +158,274 @@ pub struct Task {
     pub trade_partner_ids: Option<Vec<String>>,
 }
 
+/// Dependency link type
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LinkType {
+    /// Finish-to-Start
+    #[default]
+    #[serde(rename = "FS")]
+    Fs,
+    /// Start-to-Start
+    #[serde(rename = "SS")]
+    Ss,
+    /// Finish-to-Finish
+    #[serde(rename = "FF")]
+    Ff,
+    /// Start-to-Finish
+    #[serde(rename = "SF")]
+    Sf,
+}
+
+impl FromStr for LinkType {
+    type Err = String;
+    
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_uppercase().as_str() {
+            "FS" => Ok(LinkType::Fs),
+            "SS" => Ok(LinkType::Ss),
+            "FF" => Ok(LinkType::Ff),
+            "SF" => Ok(LinkType::Sf),
+            _ => Err(format!("unknown link type '{}' (expected FS, SS, FF or SF)", value)),
+        }
+    }
+}
+
+/// Scheduling constraint on a task
+///
+/// Parsed case-insensitively; the P6 names "Start On or After" (SOA),
+/// "Start On or Before" (SOB), "Finish On or After" (FOA) and "Finish On or
+/// Before" (FOB) map to SNET, SNLT, FNET and FNLT, and an empty value is ASAP.
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ConstraintType {
+    /// As Soon As Possible (no constraint)
+    #[default]
+    Asap,
+    /// As Late As Possible - uses up free float
+    Alap,
+    /// Start No Earlier Than - limits early start
+    Snet,
+    /// Start No Later Than - limits late start
+    Snlt,
+    /// Finish No Earlier Than - limits early finish
+    Fnet,
+    /// Finish No Later Than - limits late finish
+    Fnlt,
+    /// Must Start On - fixes early and late start
+    Mso,
+    /// Must Finish On - fixes early and late finish
+    Mfo,
+}
+
+impl FromStr for ConstraintType {
+    type Err = String;
+    
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "asap" | "" => Ok(ConstraintType::Asap),
+            "alap" => Ok(ConstraintType::Alap),
+            "snet" | "soa" => Ok(ConstraintType::Snet),
+            "snlt" | "sob" => Ok(ConstraintType::Snlt),
+            "fnet" | "foa" => Ok(ConstraintType::Fnet),
+            "fnlt" | "fob" => Ok(ConstraintType::Fnlt),
+            "mso" => Ok(ConstraintType::Mso),
+            "mfo" => Ok(ConstraintType::Mfo),
+            _ => Err(format!(
+                "unknown constraint type '{}' (expected asap, alap, snet, snlt, fnet, fnlt, mso or mfo)",
+                value
+            )),
+        }
+    }
+}
+
+/// How a task's dates are set
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SchedulingMode {
+    /// CPM calculates dates from dependencies and constraints
+    #[default]
+    Auto,
+    /// User-fixed dates - CPM skips the task's predecessor logic
+    Manual,
+}
+
+impl FromStr for SchedulingMode {
+    type Err = String;
+    
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Ok(SchedulingMode::Auto),
+            "manual" => Ok(SchedulingMode::Manual),
+            _ => Err(format!("unknown scheduling mode '{}' (expected Auto or Manual)", value)),
+        }
+    }
+}
+
+/// Kind of row in the task list
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum RowType {
+    /// Normal schedulable task
+    #[default]
+    Task,
+    /// Visual spacer row (not scheduled)
+    Blank,
+    /// Ghost row for quick add (transient, not scheduled differently from a task)
+    Phantom,
+}
+
+impl FromStr for RowType {
+    type Err = String;
+    
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "task" => Ok(RowType::Task),
+            "blank" => Ok(RowType::Blank),
+            "phantom" => Ok(RowType::Phantom),
+            _ => Err(format!("unknown row type '{}' (expected task, blank or phantom)", value)),
+        }
+    }
+}
+
+/// Deserialize a value from its (case-insensitive) string form
+fn deserialize_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+}
+
+impl<'de> Deserialize<'de> for LinkType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_str(deserializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ConstraintType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_str(deserializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SchedulingMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_str(deserializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RowType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_str(deserializer)
+    }
+}
+
+/// Overall health of a task, most severe first
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum HealthStatus {
+    /// Its logic is broken (circular or missing predecessors)
+    Blocked,
+    /// It cannot meet its dates (negative float, constraint slipped too far)
+    Critical,
+    /// A constraint slipped a little, or float is low
+    AtRisk,
+    /// Manually scheduled - the engine does not move its dates
+    Forced,
+    Healthy,
+}
+
+/// Why a task has the health it has
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthReason {
+    /// On a circular dependency (blocked)
+    InCycle,
+    /// Links to a predecessor that does not exist (blocked)
+    MissingPredecessor,
+    /// Total float below zero
+    NegativeFloat,
+    /// Finishes after its finish-no-later-than date
+    FnltSlipped,
+    /// Starts after its start-no-later-than date
+    ConstraintViolated,
+    /// Total float below the health float threshold (at risk), other than
+    /// zero float on a critical task
+    LowFloat,
+    /// Manually scheduled (forced)
+    ManualMode,
+}
+
+/// Schedule health of a task
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthIndicator {
+    pub status: HealthStatus,
+    /// Everything found, most severe first (empty when healthy)
+    pub reasons: Vec<HealthReason>,
+    pub icon: String,
+    /// One line for tooltips
+    pub summary: String,
+    /// One line per reason
+    pub details: Vec<String>,
+    /// Work days between finish (or start) and the constraint date of a
+    /// no-later-than constraint (negative = late)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint_variance: Option<i32>,
+    /// That constraint date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint_target: Option<String>,
+    /// The date compared against it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub projected_date: Option<String>,
+}
+
+/// What determined a task's early start
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum StartDriver {
+    /// One or more predecessor links (see `_predecessorLinks`)
+    Predecessor,
+    /// Predecessor logic on a parent summary
+    Summary,
+    /// The task's own constraint
+    Constraint,
+    /// Nothing earlier than the data date could be scheduled
+    DataDate,
+    /// Actual start / finish of a started task
+    Actuals,
+    /// Manually scheduled task
+    Manual,
+    /// Summary task - dates come from its children
+    Rollup,
+    /// Nothing identifiable (e.g. unscheduled task)
+    None,
+}
+
+/// Analysis of one incoming dependency link
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkAnalysis {
+    pub predecessor_id: String,
+    pub link_type: LinkType,
+    pub lag: i32,
+    /// Work days the predecessor could slip before this link moves the task
+    pub relative_float: i32,
+    /// True if this link sets the task's early start
+    pub driving: bool,
+}
+
+/// One step of a driving path, walking back from a chosen task
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DrivingPathStep {
+    pub task_id: String,
+    pub driven_by: Option<StartDriver>,
+    /// Task this step drives (None for the chosen task itself)
+    pub successor_id: Option<String>,
+    /// Link type to `successor_id`, or None when driving it as its summary
+    pub link_type: Option<LinkType>,
+}
+
 /// Calendar configuration
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
@@ -134,9 +434,306 @@ pub struct Calendar {
     #[serde(rename = "workingDays", default)]
     pub working_days: Vec<i32>,
     
-    /// Date-specific exceptions (can be CalendarException object or string)
+    /// Date-specific exceptions keyed by (first) date. Accepts the legacy
+    /// forms too: a plain string is a non-working day with that description.
+    #[serde(default, deserialize_with = "deserialize_exceptions")]
+    pub exceptions: BTreeMap<NaiveDate, CalendarException>,
+    
+    /// Working hours by weekday ("0"=Sun ... "6"=Sat), used when scheduling
+    /// in hours. Working days without an entry work 08:00-12:00, 13:00-17:00.
+    #[serde(default)]
+    pub work_hours: HashMap<String, Vec<WorkPeriod>>,
+    
+    /// Recurring exception rules (holidays every year, shutdowns, ...).
+    /// Date-specific `exceptions` take precedence over them.
+    #[serde(default)]
+    pub rules: Vec<CalendarRule>,
+}
+
+/// A date-specific calendar exception: one day or a range of days
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CalendarException {
+    /// First (or only) day "YYYY-MM-DD" - the same as its key in `exceptions`
+    #[serde(deserialize_with = "deserialize_day")]
+    pub date: NaiveDate,
+    /// Last day of a range (inclusive), None for a single day
+    #[serde(default, deserialize_with = "deserialize_optional_day", skip_serializing_if = "Option::is_none")]
+    pub end: Option<NaiveDate>,
+    /// True = working (e.g. a Saturday worked), false = non-working
+    #[serde(default)]
+    pub working: bool,
+    /// Working hours on these days when scheduling in hours
+    /// (None = the weekday's usual hours)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hours: Option<Vec<WorkPeriod>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Read `Calendar.exceptions`, reporting every malformed entry by its key
+fn deserialize_exceptions<'de, D>(deserializer: D) -> Result<BTreeMap<NaiveDate, CalendarException>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Option<BTreeMap<String, serde_json::Value>> = Option::deserialize(deserializer)?;
+    let mut exceptions = BTreeMap::new();
+    let mut errors = Vec::new();
+    
+    for (key, value) in raw.unwrap_or_default() {
+        match CalendarException::from_entry(&key, value) {
+            Ok(exception) => {
+                exceptions.insert(exception.date, exception);
+            }
+            Err(e) => errors.push(format!("exception '{}': {}", key, e)),
+        }
+    }
+    
+    if errors.is_empty() {
+        Ok(exceptions)
+    } else {
+        Err(serde::de::Error::custom(errors.join("; ")))
+    }
+}
+
+impl CalendarException {
+    /// Parse one `exceptions` entry (typed object or legacy string) and validate it
+    fn from_entry(key: &str, value: serde_json::Value) -> Result<Self, String> {
+        let day = crate::date_utils::parse_day(key)?;
+        let exception = match value {
+            // Legacy: "2024-12-25": "Christmas"
+            serde_json::Value::String(description) => CalendarException {
+                date: day,
+                end: None,
+                working: false,
+                hours: None,
+                description: Some(description).filter(|d| !d.is_empty()),
+            },
+            serde_json::Value::Object(mut fields) => {
+                fields.entry("date").or_insert_with(|| key.into());
+                serde_json::from_value(serde_json::Value::Object(fields)).map_err(|e| e.to_string())?
+            }
+            other => return Err(format!("expected an object or a description string, got {}", other)),
+        };
+        
+        if exception.date != day {
+            return Err(format!("date '{}' does not match its key", exception.date));
+        }
+        crate::date_utils::validate_exception(&exception)?;
+        Ok(exception)
+    }
+}
+
+/// A recurring calendar exception
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarRule {
+    /// Which days the rule applies to
+    pub repeat: Recurrence,
+    /// True = working on matching days (e.g. overtime), false = non-working.
+    /// Ignored by weekly overrides, which carry their own working days.
     #[serde(default)]
-    pub exceptions: serde_json::Value,
+    pub working: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Days a calendar rule applies to
+///
+/// Weekdays are 0=Sun ... 6=Sat, months 1-12, dates "YYYY-MM-DD" (inclusive).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Recurrence {
+    /// The same date every year (e.g. December 25)
+    Annual { month: u32, day: u32 },
+    /// The nth weekday of a month every year (e.g. 4th Thursday of November);
+    /// negative `nth` counts from the end of the month (-1 = last)
+    NthWeekday { month: u32, weekday: u32, nth: i32 },
+    /// Every day of a date range (e.g. a winter shutdown)
+    Range {
+        #[serde(deserialize_with = "deserialize_day")]
+        start: NaiveDate,
+        #[serde(deserialize_with = "deserialize_day")]
+        end: NaiveDate,
+    },
+    /// Different working weekdays for a period (e.g. 6-day weeks in summer)
+    #[serde(rename_all = "camelCase")]
+    Weekly {
+        #[serde(deserialize_with = "deserialize_day")]
+        start: NaiveDate,
+        #[serde(deserialize_with = "deserialize_day")]
+        end: NaiveDate,
+        working_days: Vec<i32>,
+    },
+}
+
+/// Read a "YYYY-MM-DD" calendar date, naming the value if it isn't one
+fn deserialize_day<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let date = String::deserialize(deserializer)?;
+    crate::date_utils::parse_day(&date).map_err(serde::de::Error::custom)
+}
+
+/// `deserialize_day` for an optional date
+fn deserialize_optional_day<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let date: Option<String> = Option::deserialize(deserializer)?;
+    date.map(|d| crate::date_utils::parse_day(&d)).transpose().map_err(serde::de::Error::custom)
+}
+
+/// One concrete exception day, as listed for display
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionDay {
+    pub date: String,
+    pub working: bool,
+    pub description: Option<String>,
+}
+
+/// One stretch of working time within a day ("HH:MM", end up to "24:00")
+///
+/// A night shift that runs past midnight is two periods: one ending at
+/// "24:00" and one starting at "00:00" the next weekday.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WorkPeriod {
+    pub start: String,
+    pub end: String,
+}
+
+/// Unit that durations, lags and floats are counted in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeUnit {
+    /// Whole working days; dates are "YYYY-MM-DD"
+    #[default]
+    Days,
+    /// Working hours of the calendars' work periods; dates are "YYYY-MM-DDTHH:MM"
+    Hours,
+}
+
+/// How progress on a started task interacts with its predecessor logic
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ProgressMode {
+    /// Remaining work of an out-of-sequence task still waits for its predecessors (P6 default)
+    #[default]
+    RetainedLogic,
+    /// Once started, a task's remaining work ignores its predecessors
+    ProgressOverride,
+}
+
+/// How the critical path is defined
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CriticalPathMode {
+    /// Critical = total float <= `criticalFloatThreshold`
+    #[default]
+    TotalFloat,
+    /// Critical = on the driving chain that ends at the project finish
+    LongestPath,
+}
+
+/// Calendar a link's lag is counted on
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LagCalendar {
+    /// The predecessor's calendar (P6 default)
+    #[default]
+    Predecessor,
+    /// The successor's calendar
+    Successor,
+    /// Every calendar day counts (curing, drying)
+    #[serde(rename = "24Hour")]
+    TwentyFourHour,
+}
+
+/// What loading tasks does with an invalid hierarchy (see `hierarchy.rs`)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HierarchyPolicy {
+    /// Refuse the tasks, listing every problem found
+    Reject,
+    /// Load the tasks, re-attaching each task whose parent is invalid
+    #[default]
+    Repair,
+}
+
+/// Options that control a CPM calculation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleOptions {
+    /// Progress handling for in-progress tasks
+    #[serde(default)]
+    pub progress_mode: ProgressMode,
+    
+    /// Critical path definition
+    #[serde(default)]
+    pub critical_path: CriticalPathMode,
+    
+    /// Total float (work days) at or below which a task is critical
+    /// (total float mode only)
+    #[serde(default)]
+    pub critical_float_threshold: i32,
+    
+    /// Total float (work days) at or below which a non-critical task is near-critical
+    #[serde(default = "default_near_critical_threshold")]
+    pub near_critical_threshold: i32,
+    
+    /// Calendar that link lags are counted on
+    #[serde(default)]
+    pub lag_calendar: LagCalendar,
+    
+    /// Unit of durations, lags and floats
+    #[serde(default)]
+    pub time_unit: TimeUnit,
+    
+    /// Handling of an invalid hierarchy on `initialize` / `sync_tasks`
+    #[serde(default)]
+    pub hierarchy_policy: HierarchyPolicy,
+    
+    /// Work days a no-later-than constraint can slip while the task is only
+    /// at risk; beyond it the task is a critical failure
+    #[serde(default = "default_health_slip_threshold")]
+    pub health_slip_threshold: i32,
+    
+    /// Total float (work days) below which a task is at risk
+    ///
+    /// A critical task with zero float is not at risk: that is what being on
+    /// the critical path means.
+    #[serde(default = "default_health_float_threshold")]
+    pub health_float_threshold: i32,
+}
+
+impl Default for ScheduleOptions {
+    fn default() -> Self {
+        ScheduleOptions {
+            progress_mode: ProgressMode::default(),
+            critical_path: CriticalPathMode::default(),
+            critical_float_threshold: 0,
+            near_critical_threshold: default_near_critical_threshold(),
+            lag_calendar: LagCalendar::default(),
+            time_unit: TimeUnit::default(),
+            hierarchy_policy: HierarchyPolicy::default(),
+            health_slip_threshold: default_health_slip_threshold(),
+            health_float_threshold: default_health_float_threshold(),
+        }
+    }
+}
+
+fn default_near_critical_threshold() -> i32 {
+    5
+}
+
+fn default_health_slip_threshold() -> i32 {
+    3
+}
+
+fn default_health_float_threshold() -> i32 {
+    2
 }
 
 /// CPM calculation statistics
@@ -146,20 +743,205 @@ pub struct CPMStats {
     pub calc_time: f64,
     pub task_count: i32,
     pub critical_count: i32,
+    /// Leaf tasks within the near-critical float threshold
+    #[serde(default)]
+    pub near_critical_count: i32,
+    /// Leaf tasks that are neither critical nor near-critical
+    #[serde(default)]
+    pub non_critical_count: i32,
+    /// Number of tasks finishing after their deadline
+    #[serde(default)]
+    pub missed_deadline_count: i32,
     pub project_end: String,
     pub duration: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
-/// Default scheduling mode for new/imported tasks
-fn default_scheduling_mode() -> String {
-    "Auto".to_string()
+/// One link in a circular dependency chain
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleLink {
+    pub from_id: String,
+    pub to_id: String,
+    /// Link type of the dependency, or None for the implicit
+    /// child -> parent summary relationship
+    pub link_type: Option<LinkType>,
+}
+
+/// A circular dependency detected before scheduling
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyCycle {
+    /// Task IDs in chain order - the last task links back to the first
+    pub task_ids: Vec<String>,
+    /// Links along the chain; `links[i]` starts at `task_ids[i]`
+    pub links: Vec<CycleLink>,
+}
+
+/// Kind of problem with a task's `parentId`
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HierarchyIssueKind {
+    /// The task names itself as its parent
+    SelfParent,
+    /// Following parents leads back to the task
+    ParentCycle,
+    /// No task has the parent's ID
+    DanglingParent,
+    /// The parent is a blank row
+    BlankParent,
+}
+
+/// A problem found in the task hierarchy
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchyIssue {
+    pub kind: HierarchyIssueKind,
+    /// Task whose `parentId` is at fault
+    pub task_id: String,
+    /// The `parentId` it names
+    pub parent_id: String,
+    /// Task IDs around a parent cycle, starting with `task_id` - each
+    /// task's parent is the next one, the last one's is the first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cycle: Vec<String>,
+    /// Whether the task was re-attached (policy "repair")
+    pub repaired: bool,
+    pub message: String,
+}
+
+/// Kind of problem with a dependency link
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogicIssueKind {
+    /// No task has the predecessor's ID
+    DanglingPredecessor,
+    /// The task links to itself
+    SelfLink,
+    /// The task links to the same predecessor more than once
+    DuplicateLink,
+    /// The link joins a task and one of its summaries
+    AncestorLink,
+    /// Either end of the link is a blank row
+    BlankRowLink,
+    /// The lag (or lead) is longer than the task's own duration (not checked
+    /// on milestones)
+    LagExceedsDuration,
+}
+
+/// A problem found with a dependency link
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicIssue {
+    pub kind: LogicIssueKind,
+    /// Task holding the link (the successor)
+    pub task_id: String,
+    /// Predecessor the link names
+    pub predecessor_id: String,
+    pub link_type: LinkType,
+    pub lag: i32,
+    pub message: String,
+}
+
+/// One of the DCMA 14-point schedule checks, by number
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DcmaMetric {
+    Logic,
+    Leads,
+    Lags,
+    RelationshipTypes,
+    HardConstraints,
+    HighFloat,
+    NegativeFloat,
+    HighDuration,
+    InvalidDates,
+    Resources,
+    MissedTasks,
+    CriticalPathTest,
+    Cpli,
+    Bei,
+}
+
+/// Result of one DCMA check
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DcmaCheck {
+    /// Check number (1-14)
+    pub number: u8,
+    pub metric: DcmaMetric,
+    pub passed: bool,
+    /// Measured value: a percentage, or an index for CPLI and BEI
+    pub value: f64,
+    /// Limit the value is held to (a maximum, or a minimum for
+    /// relationship types, CPLI and BEI)
+    pub threshold: f64,
+    /// Tasks that count against the check
+    pub task_ids: Vec<String>,
+    pub message: String,
 }
 
 /// CPM calculation result
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CPMResult {
+    /// Tasks whose values changed (every task when `full` is set)
     pub tasks: Vec<Task>,
     pub stats: CPMStats,
+    /// Circular dependencies found in the network (empty when the logic is sound)
+    #[serde(default)]
+    pub cycles: Vec<DependencyCycle>,
+    /// True when every task was recalculated and `tasks` is the whole project;
+    /// false for an incremental result to merge by ID
+    #[serde(default)]
+    pub full: bool,
+}
+
+/// One operation of an atomic batch (`SchedulerEngine::apply_batch`)
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum BatchOperation {
+    /// Add a new task
+    Create { task: Box<Task> },
+    /// Patch fields of a task (same rules as `update_task`)
+    Update { id: String, updates: serde_json::Value },
+    /// Remove a task
+    Delete { id: String },
+    /// Re-parent and/or reorder a task (indent, outdent, drag);
+    /// a null `parentId` moves it to the root
+    #[serde(rename_all = "camelCase")]
+    Move {
+        id: String,
+        #[serde(default)]
+        parent_id: Option<String>,
+        #[serde(default)]
+        sort_key: Option<String>,
+    },
+}
+
+impl BatchOperation {
+    /// Operation name as written in JS ("create", "update", ...)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BatchOperation::Create { .. } => "create",
+            BatchOperation::Update { .. } => "update",
+            BatchOperation::Delete { .. } => "delete",
+            BatchOperation::Move { .. } => "move",
+        }
+    }
+}
+
+/// Changes made by an applied batch
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    /// IDs of created tasks, in operation order
+    pub created: Vec<String>,
+    /// IDs of updated or moved tasks, in operation order
+    pub updated: Vec<String>,
+    /// IDs of deleted tasks, in operation order
+    pub deleted: Vec<String>,
+    /// Calculation result, when the batch asked to recalculate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<CPMResult>,
 }
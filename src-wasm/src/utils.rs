@@ -1,4 +1,4 @@
-//! Utility functions for WASM error handling
+//! Utility functions for WASM error handling and timing
 
 /// Set panic hook for better error messages in WASM
 /// 
@@ -12,3 +12,25 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+/// Milliseconds since the Unix epoch, for timing calculations
+///
+/// `std::time` has no clock in WASM, so the JS clock is used there.
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64() * 1000.0)
+}
+
+/// Today's date ("YYYY-MM-DD", UTC)
+///
+/// Only read when a project is loaded, never during a calculation.
+pub fn today() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
@@ -134,16 +134,19 @@ export const PasteCommand: Command = {
             }
         });
 
-        // Add new tasks
-        const allTasks = ctx.controller.getTasks();
-        const finalTasks = [...allTasks, ...newTasks];
-        ctx.controller.syncTasks(finalTasks);
+        // Add new tasks and, if cut, delete the originals - as one batch
+        ctx.controller.batch(isCut ? 'Move Tasks' : 'Paste Tasks', () => {
+            newTasks.forEach(task => {
+                ctx.controller.addTask(task);
+            });
+            if (isCut) {
+                originalIds.forEach(id => {
+                    ctx.controller.deleteTask(id);
+                });
+            }
+        });
 
-        // If cut: delete originals and clear clipboard
         if (isCut) {
-            originalIds.forEach(id => {
-                ctx.controller.deleteTask(id);
-            });
             ctx.clipboardManager.clear();
         }
 
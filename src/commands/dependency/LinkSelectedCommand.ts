@@ -59,30 +59,32 @@ export const LinkSelectedCommand: Command = {
             let linksCreated = 0;
 
             // Create links: task[0] → task[1] → task[2] → ...
-            for (let i = 0; i < linkable.length - 1; i++) {
-                const predecessorId = linkable[i];
-                const successorId = linkable[i + 1];
-                const successor = controller.getTaskById(successorId);
-
-                if (!successor) continue;
-
-                // Skip if link already exists
-                const existingDeps = successor.dependencies || [];
-                if (existingDeps.some(d => d.id === predecessorId)) {
-                    continue;
+            controller.batch(`Link ${linkable.length} Tasks`, () => {
+                for (let i = 0; i < linkable.length - 1; i++) {
+                    const predecessorId = linkable[i];
+                    const successorId = linkable[i + 1];
+                    const successor = controller.getTaskById(successorId);
+
+                    if (!successor) continue;
+
+                    // Skip if link already exists
+                    const existingDeps = successor.dependencies || [];
+                    if (existingDeps.some(d => d.id === predecessorId)) {
+                        continue;
+                    }
+
+                    // Create new FS dependency
+                    const newDep: Dependency = {
+                        id: predecessorId,
+                        type: 'FS' as LinkType,
+                        lag: 0
+                    };
+
+                    const newDeps = [...existingDeps, newDep];
+                    controller.updateTask(successorId, { dependencies: newDeps });
+                    linksCreated++;
                 }
-
-                // Create new FS dependency
-                const newDep: Dependency = {
-                    id: predecessorId,
-                    type: 'FS' as LinkType,
-                    lag: 0
-                };
-
-                const newDeps = [...existingDeps, newDep];
-                controller.updateTask(successorId, { dependencies: newDeps });
-                linksCreated++;
-            }
+            });
 
             if (linksCreated === 0) {
                 toastService?.info('Tasks are already linked');
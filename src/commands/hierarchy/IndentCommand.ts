@@ -60,42 +60,44 @@ export const IndentCommand: Command = {
 
         let indentedCount = 0;
 
-        for (const task of topLevelSelected) {
-            const idx = list.findIndex(t => t.id === task.id);
-            if (idx <= 0) continue;
-
-            const prev = list[idx - 1];
-            const taskDepth = ctx.controller.getDepth(task.id);
-            const prevDepth = ctx.controller.getDepth(prev.id);
-
-            // Can only indent if prev is at same or higher depth
-            if (prevDepth < taskDepth) continue;
-
-            let newParentId: string | null = null;
-            if (prevDepth === taskDepth) {
-                // Prev becomes the new parent
-                newParentId = prev.id;
-            } else {
-                // Walk up to find appropriate parent at same depth
-                let curr = prev;
-                while (curr && ctx.controller.getDepth(curr.id) > taskDepth) {
-                    const parentId = curr.parentId;
-                    if (!parentId) break;
-                    const parent = ctx.controller.getTaskById(parentId);
-                    if (!parent) break;
-                    curr = parent;
+        ctx.controller.batch('Indent Tasks', () => {
+            for (const task of topLevelSelected) {
+                const idx = list.findIndex(t => t.id === task.id);
+                if (idx <= 0) continue;
+
+                const prev = list[idx - 1];
+                const taskDepth = ctx.controller.getDepth(task.id);
+                const prevDepth = ctx.controller.getDepth(prev.id);
+
+                // Can only indent if prev is at same or higher depth
+                if (prevDepth < taskDepth) continue;
+
+                let newParentId: string | null = null;
+                if (prevDepth === taskDepth) {
+                    // Prev becomes the new parent
+                    newParentId = prev.id;
+                } else {
+                    // Walk up to find appropriate parent at same depth
+                    let curr = prev;
+                    while (curr && ctx.controller.getDepth(curr.id) > taskDepth) {
+                        const parentId = curr.parentId;
+                        if (!parentId) break;
+                        const parent = ctx.controller.getTaskById(parentId);
+                        if (!parent) break;
+                        curr = parent;
+                    }
+                    if (curr) newParentId = curr.id;
                 }
-                if (curr) newParentId = curr.id;
-            }
 
-            if (newParentId !== null) {
-                const newSortKey = ctx.orderingService.generateAppendKey(
-                    ctx.controller.getLastSortKey(newParentId)
-                );
-                ctx.controller.moveTask(task.id, newParentId, newSortKey);
-                indentedCount++;
+                if (newParentId !== null) {
+                    const newSortKey = ctx.orderingService.generateAppendKey(
+                        ctx.controller.getLastSortKey(newParentId)
+                    );
+                    ctx.controller.moveTask(task.id, newParentId, newSortKey);
+                    indentedCount++;
+                }
             }
-        }
+        });
 
         if (indentedCount > 0) {
             ctx.toastService?.success(`Indented ${indentedCount} task${indentedCount > 1 ? 's' : ''}`);
@@ -48,29 +48,31 @@ export const OutdentCommand: Command = {
 
         let outdentedCount = 0;
 
-        for (const task of topLevelSelected) {
-            if (!task.parentId) continue; // Already at root
-
-            const currentParent = allTasks.find(t => t.id === task.parentId);
-            const grandparentId = currentParent?.parentId ?? null;
-
-            // Position after former parent among its siblings
-            const auntsUncles = ctx.controller.getChildren(grandparentId);
-            const formerParentIndex = auntsUncles.findIndex(t => t.id === currentParent?.id);
-
-            const beforeKey = currentParent?.sortKey ?? null;
-            const afterKey = formerParentIndex < auntsUncles.length - 1
-                ? auntsUncles[formerParentIndex + 1].sortKey
-                : null;
-
-            const newSortKey = ctx.orderingService.generateInsertKey(beforeKey, afterKey);
-
-            ctx.controller.updateTask(task.id, {
-                parentId: grandparentId,
-                sortKey: newSortKey
-            });
-            outdentedCount++;
-        }
+        ctx.controller.batch('Outdent Tasks', () => {
+            for (const task of topLevelSelected) {
+                if (!task.parentId) continue; // Already at root
+
+                const currentParent = allTasks.find(t => t.id === task.parentId);
+                const grandparentId = currentParent?.parentId ?? null;
+
+                // Position after former parent among its siblings
+                const auntsUncles = ctx.controller.getChildren(grandparentId);
+                const formerParentIndex = auntsUncles.findIndex(t => t.id === currentParent?.id);
+
+                const beforeKey = currentParent?.sortKey ?? null;
+                const afterKey = formerParentIndex < auntsUncles.length - 1
+                    ? auntsUncles[formerParentIndex + 1].sortKey
+                    : null;
+
+                const newSortKey = ctx.orderingService.generateInsertKey(beforeKey, afterKey);
+
+                ctx.controller.updateTask(task.id, {
+                    parentId: grandparentId,
+                    sortKey: newSortKey
+                });
+                outdentedCount++;
+            }
+        });
 
         if (outdentedCount > 0) {
             ctx.toastService?.success(`Outdented ${outdentedCount} task${outdentedCount > 1 ? 's' : ''}`);
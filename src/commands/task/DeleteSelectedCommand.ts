@@ -3,7 +3,7 @@
  * @module commands/task/DeleteSelectedCommand
  * 
  * Deletes all currently selected tasks.
- * Uses composite actions for proper undo/redo grouping.
+ * Uses one batch for proper undo/redo grouping and an atomic worker update.
  */
 
 import type { Command, CommandContext, CommandResult } from '../types';
@@ -37,31 +37,23 @@ export const DeleteSelectedCommand: Command = {
             return { success: false, message: 'No tasks selected' };
         }
 
-        // Begin composite for single undo
-        ctx.historyManager?.beginComposite(`Delete ${selectedIds.length} Task(s)`);
-
-        try {
-            // Delete each task through ProjectController
-            // ProjectController handles optimistic updates + worker + persistence
+        // Delete each task through ProjectController, as one batch (single undo)
+        // ProjectController handles optimistic updates + worker + persistence
+        ctx.controller.batch(`Delete ${selectedIds.length} Task(s)`, () => {
             for (const id of selectedIds) {
                 ctx.controller.deleteTask(id);
             }
+        });
 
-            ctx.historyManager?.endComposite();
-
-            // Clear selection and focus
-            ctx.selection.clear();
-            ctx.selection.setFocus(null);
+        // Clear selection and focus
+        ctx.selection.clear();
+        ctx.selection.setFocus(null);
 
-            ctx.toastService?.success(`Deleted ${selectedIds.length} task(s)`);
+        ctx.toastService?.success(`Deleted ${selectedIds.length} task(s)`);
 
-            return { 
-                success: true, 
-                data: { deletedCount: selectedIds.length } 
-            };
-        } catch (error) {
-            ctx.historyManager?.cancelComposite();
-            throw error;
-        }
+        return { 
+            success: true, 
+            data: { deletedCount: selectedIds.length } 
+        };
     }
 };
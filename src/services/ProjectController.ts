@@ -15,10 +15,11 @@
 
 import { BehaviorSubject, Subject, filter, firstValueFrom, timeout } from 'rxjs';
 import type { Task, Calendar, CPMResult, ConstraintType, SchedulingMode } from '../types';
-import type { WorkerCommand, WorkerResponse } from '../workers/types';
+import type { BatchOperation, DcmaCheck, LogicIssue, WorkerCommand, WorkerResponse } from '../workers/types';
 import type { PersistenceService } from '../data/PersistenceService';
 import type { HistoryManager, QueuedEvent } from '../data/HistoryManager';
 import type { ToastService } from '../ui/services/ToastService';
+import { DateUtils } from '../core/DateUtils';
 
 /**
  * ProjectController
@@ -51,6 +52,9 @@ export class ProjectController {
         exceptions: {}
     });
     
+    /** Project data (status) date - no unfinished work is scheduled before it */
+    public readonly dataDate$ = new BehaviorSubject<string>(DateUtils.today());
+    
     /** CPM statistics from last calculation */
     public readonly stats$ = new BehaviorSubject<CPMResult['stats'] | null>(null);
     
@@ -62,6 +66,12 @@ export class ProjectController {
     
     /** Error stream for error handling */
     public readonly errors$ = new Subject<string>();
+    
+    /** Dependency link problems from the last `validateLogic()` */
+    public readonly logicIssues$ = new BehaviorSubject<LogicIssue[]>([]);
+    
+    /** DCMA 14-point results from the last `runDcmaCheck()` */
+    public readonly dcmaChecks$ = new BehaviorSubject<DcmaCheck[]>([]);
 
     // Event stream for specific worker responses (for awaiting async operations)
     private readonly workerResponses$ = new Subject<WorkerResponse>();
@@ -81,12 +91,15 @@ export class ProjectController {
     
     /** Pending operation tracking for rollback on worker errors */
     private pendingOperation: {
-        type: 'ADD' | 'UPDATE' | 'DELETE';
+        type: 'ADD' | 'UPDATE' | 'DELETE' | 'BATCH';
         taskId: string;
         snapshot: Task[];
         wasComposite: boolean;
     } | null = null;
 
+    /** Operations collected by an open batch (see batch()) */
+    private batchOperations: BatchOperation[] | null = null;
+
     // ========================================================================
     // Constructor & Singleton
     // ========================================================================
@@ -280,7 +293,13 @@ export class ProjectController {
 
             case 'CALCULATION_RESULT':
                 // Update the "hot" state that drives the UI
-                this.tasks$.next(response.payload.tasks);
+                if (response.payload.full) {
+                    this.tasks$.next(response.payload.tasks);
+                } else {
+                    // Incremental result: only the tasks that changed
+                    const changed = new Map(response.payload.tasks.map(t => [t.id, t]));
+                    this.tasks$.next(this.tasks$.value.map(t => changed.get(t.id) ?? t));
+                }
                 this.stats$.next(response.payload.stats);
                 this.isCalculating$.next(false);
                 
@@ -294,6 +313,28 @@ export class ProjectController {
                 );
                 break;
 
+            case 'LOGIC_VALIDATED':
+                this.logicIssues$.next(response.payload);
+                break;
+
+            case 'DCMA_CHECKED':
+                this.dcmaChecks$.next(response.payload);
+                break;
+
+            case 'BATCH_APPLIED': {
+                const { created, updated, deleted, result } = response.payload;
+                console.log(
+                    `[ProjectController] Batch applied: ${created.length} created, ` +
+                    `${updated.length} updated, ${deleted.length} deleted`
+                );
+                // The recalculated schedule follows as CALCULATION_RESULT
+                if (!result) {
+                    this.isCalculating$.next(false);
+                }
+                this.pendingOperation = null;
+                break;
+            }
+
             case 'TASKS_SYNCED':
                 console.log('[ProjectController] Tasks synced');
                 // Clear pending operation on sync success
@@ -327,6 +368,34 @@ export class ProjectController {
         this.worker.postMessage(command);
     }
 
+    /**
+     * Send a single edit to the worker, or collect it when a batch is open
+     */
+    private sendEdit(operation: BatchOperation): void {
+        if (this.batchOperations) {
+            this.batchOperations.push(operation);
+            return;
+        }
+        this.isCalculating$.next(true);
+        switch (operation.op) {
+            case 'create':
+                this.send({ type: 'ADD_TASK', payload: operation.task });
+                break;
+            case 'update':
+                this.send({ type: 'UPDATE_TASK', payload: { id: operation.id, updates: operation.updates } });
+                break;
+            case 'delete':
+                this.send({ type: 'DELETE_TASK', payload: { id: operation.id } });
+                break;
+            case 'move':
+                this.send({
+                    type: 'UPDATE_TASK',
+                    payload: { id: operation.id, updates: { parentId: operation.parentId, sortKey: operation.sortKey } }
+                });
+                break;
+        }
+    }
+
     // ========================================================================
     // Public API - Initialization
     // ========================================================================
@@ -336,15 +405,17 @@ export class ProjectController {
      * 
      * @param tasks - Initial task list
      * @param calendar - Calendar configuration
+     * @param dataDate - Project data date ("YYYY-MM-DD"), today when omitted
      * @returns Promise that resolves when initialization is complete
      */
-    public async initialize(tasks: Task[], calendar: Calendar): Promise<void> {
+    public async initialize(tasks: Task[], calendar: Calendar, dataDate?: string): Promise<void> {
         console.log(`[ProjectController] Initializing with ${tasks.length} tasks`);
         
-        // Store calendar locally for snapshot access
+        // Store calendar and data date locally for snapshot access
         this.calendar$.next(calendar);
+        this.dataDate$.next(dataDate ?? this.dataDate$.value);
         
-        this.send({ type: 'INITIALIZE', payload: { tasks, calendar } });
+        this.send({ type: 'INITIALIZE', payload: { tasks, calendar, dataDate: this.dataDate$.value } });
         
         // Wait for initialization to complete (with timeout)
         try {
@@ -384,8 +455,7 @@ export class ProjectController {
         this.tasks$.next(currentTasks);
         
         // Send to worker for CPM calculation
-        this.isCalculating$.next(true);
-        this.send({ type: 'ADD_TASK', payload: task });
+        this.sendEdit({ op: 'create', task });
 
         // Build the event payload
         const eventPayload = {
@@ -461,8 +531,7 @@ export class ProjectController {
         }
         
         // Send to worker for CPM calculation
-        this.isCalculating$.next(true);
-        this.send({ type: 'UPDATE_TASK', payload: { id, updates } });
+        this.sendEdit({ op: 'update', id, updates });
 
         // Map task property names to DB field names for persistence
         const propToFieldMap: Record<string, string> = {
@@ -504,7 +573,7 @@ export class ProjectController {
 
         // FIX: Wrap multi-field updates in a composite action for single undo
         // This prevents needing to click undo multiple times for one logical edit
-        const needsComposite = changedUpdates.length > 1 && this.historyManager;
+        const needsComposite = changedUpdates.length > 1 && this.historyManager && !this.historyManager.isInComposite();
         if (needsComposite) {
             this.historyManager!.beginComposite('Update Task');
         }
@@ -543,6 +612,9 @@ export class ProjectController {
     public deleteTask(id: string): void {
         // Capture task snapshot BEFORE deletion for undo
         const taskToDelete = this.getTaskById(id);
+        if (!taskToDelete && this.batchOperations) {
+            return; // Already deleted with its parent earlier in the batch
+        }
         const descendants = this.getDescendants(id);
         
         // Store snapshot before optimistic update (for rollback on error)
@@ -561,8 +633,7 @@ export class ProjectController {
         this.tasks$.next(currentTasks);
         
         // Send to worker for CPM calculation
-        this.isCalculating$.next(true);
-        this.send({ type: 'DELETE_TASK', payload: { id } });
+        this.sendEdit({ op: 'delete', id });
 
         // Queue TASK_DELETED event for persistence
         if (this.persistenceService) {
@@ -611,6 +682,60 @@ export class ProjectController {
         this.send({ type: 'SYNC_TASKS', payload: { tasks } });
     }
 
+    /**
+     * Apply several edits as one atomic batch
+     * The edits made by `edit` (addTask, updateTask, deleteTask, moveTask) are
+     * applied optimistically as usual, recorded as a single undo action and
+     * sent to the worker as one APPLY_BATCH. If the worker rejects any of
+     * them, the whole batch is rolled back.
+     * @param label - Human-readable label for the undo action
+     * @param edit - Makes the edits
+     */
+    public batch(label: string, edit: () => void): void {
+        if (this.batchOperations) {
+            edit(); // Nested: part of the enclosing batch
+            return;
+        }
+
+        const snapshot = [...this.tasks$.value];
+        const wasComposite = this.historyManager?.isInComposite() || false;
+        if (!wasComposite) {
+            this.historyManager?.beginComposite(label);
+        }
+
+        this.batchOperations = [];
+        let operations: BatchOperation[];
+        try {
+            edit();
+        } catch (error) {
+            if (!wasComposite) {
+                this.historyManager?.cancelComposite();
+            }
+            this.tasks$.next(snapshot);
+            throw error;
+        } finally {
+            operations = this.batchOperations;
+            this.batchOperations = null;
+        }
+
+        if (!wasComposite) {
+            this.historyManager?.endComposite();
+        }
+        if (operations.length === 0) {
+            return;
+        }
+
+        const first = operations[0];
+        this.pendingOperation = {
+            type: 'BATCH',
+            taskId: first.op === 'create' ? first.task.id : first.id,
+            snapshot,
+            wasComposite
+        };
+        this.isCalculating$.next(true);
+        this.send({ type: 'APPLY_BATCH', payload: { operations, dataDate: this.dataDate$.value } });
+    }
+
     // ========================================================================
     // Public API - Event Application (for Undo/Redo)
     // ========================================================================
@@ -871,7 +996,31 @@ export class ProjectController {
      */
     public forceRecalculate(): void {
         this.isCalculating$.next(true);
-        this.send({ type: 'CALCULATE' });
+        this.send({ type: 'CALCULATE', payload: { dataDate: this.dataDate$.value } });
+    }
+
+    /**
+     * Move the project data date and recalculate from it
+     * 
+     * @param dataDate - New data date ("YYYY-MM-DD")
+     */
+    public setDataDate(dataDate: string): void {
+        this.dataDate$.next(dataDate);
+        this.forceRecalculate();
+    }
+
+    /**
+     * Check every dependency link - results arrive on `logicIssues$`
+     */
+    public validateLogic(): void {
+        this.send({ type: 'VALIDATE_LOGIC' });
+    }
+
+    /**
+     * Run the DCMA 14-point assessment - results arrive on `dcmaChecks$`
+     */
+    public runDcmaCheck(): void {
+        this.send({ type: 'DCMA_CHECK' });
     }
 
     // ========================================================================
@@ -1043,8 +1192,7 @@ export class ProjectController {
         this.tasks$.next(currentTasks);
 
         // Send to worker
-        this.isCalculating$.next(true);
-        this.send({ type: 'ADD_TASK', payload: blankRow });
+        this.sendEdit({ op: 'create', task: blankRow });
 
         const eventPayload = {
             id: blankRow.id,
@@ -1103,8 +1251,7 @@ export class ProjectController {
         }
 
         // Send to worker
-        this.isCalculating$.next(true);
-        this.send({ type: 'UPDATE_TASK', payload: { id: taskId, updates } });
+        this.sendEdit({ op: 'update', id: taskId, updates });
 
         // Queue persistence
         if (this.persistenceService) {
@@ -1166,8 +1313,7 @@ export class ProjectController {
         }
 
         // Send to worker
-        this.isCalculating$.next(true);
-        this.send({ type: 'UPDATE_TASK', payload: { id: taskId, updates } });
+        this.sendEdit({ op: 'move', id: taskId, parentId: newParentId, sortKey: newSortKey });
 
         const forwardPayload = {
             old_parent_id: oldParentId,
@@ -1272,7 +1418,13 @@ export class ProjectController {
         this.tasks$.next(snapshot);
         
         // 2. Cancel history (if composite active, cancel it; otherwise undo last action)
-        if (this.historyManager) {
+        if (this.historyManager && type === 'BATCH') {
+            // The worker rejected the whole batch, so there is nothing to undo there.
+            // Inside an outer composite, history is left to whoever opened it
+            if (!wasComposite && this.historyManager.canUndo()) {
+                this.historyManager.undo();
+            }
+        } else if (this.historyManager) {
             if (wasComposite && this.historyManager.isInComposite()) {
                 this.historyManager.cancelComposite();
                 console.log('[ProjectController] Cancelled composite action');
@@ -1310,15 +1462,20 @@ export class ProjectController {
      * 
      * @private
      */
-    private _formatErrorMessage(operationType: 'ADD' | 'UPDATE' | 'DELETE', errorMessage: string): string {
-        const operationLabels: Record<'ADD' | 'UPDATE' | 'DELETE', string> = {
+    private _formatErrorMessage(operationType: 'ADD' | 'UPDATE' | 'DELETE' | 'BATCH', errorMessage: string): string {
+        const operationLabels: Record<'ADD' | 'UPDATE' | 'DELETE' | 'BATCH', string> = {
             ADD: 'Adding task',
             UPDATE: 'Updating task',
-            DELETE: 'Deleting task'
+            DELETE: 'Deleting task',
+            BATCH: 'Applying changes'
         };
         
         // Extract meaningful error message (remove technical details if present)
+        // (a batch error is kept as is: it names the operation that failed)
         let message = errorMessage;
+        if (operationType === 'BATCH') {
+            return `${operationLabels[operationType]} failed: ${message}`;
+        }
         if (message.includes('WASM')) {
             message = 'Calculation engine error';
         } else if (message.includes('not found')) {
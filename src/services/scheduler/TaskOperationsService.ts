@@ -225,14 +225,16 @@ export class TaskOperationsService {
         
         const idsToDelete = selectionModel.getSelectedIds();
         
-        for (const taskId of idsToDelete) {
-            if (editingStateManager.isEditingTask(taskId)) {
-                editingStateManager.exitEditMode('task-deleted');
+        projectController.batch(`Delete ${idsToDelete.length} Task(s)`, () => {
+            for (const taskId of idsToDelete) {
+                if (editingStateManager.isEditingTask(taskId)) {
+                    editingStateManager.exitEditMode('task-deleted');
+                }
+                projectController.deleteTask(taskId);
+                selectionModel.removeFromSelection([taskId]);
+                // NOTE: Removed engine sync - ProjectController handles via Worker
             }
-            projectController.deleteTask(taskId);
-            selectionModel.removeFromSelection([taskId]);
-            // NOTE: Removed engine sync - ProjectController handles via Worker
-        }
+        });
         
         const currentFocusedId = selectionModel.getFocusedId();
         if (currentFocusedId && idsToDelete.includes(currentFocusedId)) {
@@ -357,36 +359,38 @@ export class TaskOperationsService {
         
         // Process in visual order (top to bottom)
         let indentedCount = 0;
-        for (const task of topLevelSelected) {
-            const idx = list.findIndex(t => t.id === task.id);
-            if (idx <= 0) continue;
-            
-            const prev = list[idx - 1];
-            const taskDepth = projectController.getDepth(task.id);
-            const prevDepth = projectController.getDepth(prev.id);
-            
-            // Can only indent if prev is at same or higher depth
-            if (prevDepth < taskDepth) continue;
-            
-            let newParentId: string | null = null;
-            if (prevDepth === taskDepth) {
-                newParentId = prev.id;
-            } else {
-                let curr: Task | undefined = prev;
-                while (curr && projectController.getDepth(curr.id) > taskDepth) {
-                    curr = curr.parentId ? projectController.getTaskById(curr.parentId) : undefined;
+        projectController.batch('Indent Tasks', () => {
+            for (const task of topLevelSelected) {
+                const idx = list.findIndex(t => t.id === task.id);
+                if (idx <= 0) continue;
+                
+                const prev = list[idx - 1];
+                const taskDepth = projectController.getDepth(task.id);
+                const prevDepth = projectController.getDepth(prev.id);
+                
+                // Can only indent if prev is at same or higher depth
+                if (prevDepth < taskDepth) continue;
+                
+                let newParentId: string | null = null;
+                if (prevDepth === taskDepth) {
+                    newParentId = prev.id;
+                } else {
+                    let curr: Task | undefined = prev;
+                    while (curr && projectController.getDepth(curr.id) > taskDepth) {
+                        curr = curr.parentId ? projectController.getTaskById(curr.parentId) : undefined;
+                    }
+                    if (curr) newParentId = curr.id;
+                }
+                
+                if (newParentId !== null) {
+                    const newSortKey = OrderingService.generateAppendKey(
+                        projectController.getLastSortKey(newParentId)
+                    );
+                    projectController.moveTask(task.id, newParentId, newSortKey);
+                    indentedCount++;
                 }
-                if (curr) newParentId = curr.id;
-            }
-            
-            if (newParentId !== null) {
-                const newSortKey = OrderingService.generateAppendKey(
-                    projectController.getLastSortKey(newParentId)
-                );
-                projectController.moveTask(task.id, newParentId, newSortKey);
-                indentedCount++;
             }
-        }
+        });
         
         if (indentedCount > 0) {
             // NOTE: ProjectController handles recalc/save via Worker
@@ -419,29 +423,31 @@ export class TaskOperationsService {
         );
         
         let outdentedCount = 0;
-        for (const task of topLevelSelected) {
-            if (!task.parentId) continue; // Already at root
-            
-            const currentParent = allTasks.find(t => t.id === task.parentId);
-            const grandparentId = currentParent ? currentParent.parentId : null;
-            
-            // Position after former parent among its siblings
-            const auntsUncles = projectController.getChildren(grandparentId);
-            const formerParentIndex = auntsUncles.findIndex(t => t.id === currentParent?.id);
-            
-            const beforeKey = currentParent?.sortKey ?? null;
-            const afterKey = formerParentIndex < auntsUncles.length - 1
-                ? auntsUncles[formerParentIndex + 1].sortKey
-                : null;
-            
-            const newSortKey = OrderingService.generateInsertKey(beforeKey, afterKey);
-            
-            projectController.updateTask(task.id, {
-                parentId: grandparentId,
-                sortKey: newSortKey
-            });
-            outdentedCount++;
-        }
+        projectController.batch('Outdent Tasks', () => {
+            for (const task of topLevelSelected) {
+                if (!task.parentId) continue; // Already at root
+                
+                const currentParent = allTasks.find(t => t.id === task.parentId);
+                const grandparentId = currentParent ? currentParent.parentId : null;
+                
+                // Position after former parent among its siblings
+                const auntsUncles = projectController.getChildren(grandparentId);
+                const formerParentIndex = auntsUncles.findIndex(t => t.id === currentParent?.id);
+                
+                const beforeKey = currentParent?.sortKey ?? null;
+                const afterKey = formerParentIndex < auntsUncles.length - 1
+                    ? auntsUncles[formerParentIndex + 1].sortKey
+                    : null;
+                
+                const newSortKey = OrderingService.generateInsertKey(beforeKey, afterKey);
+                
+                projectController.updateTask(task.id, {
+                    parentId: grandparentId,
+                    sortKey: newSortKey
+                });
+                outdentedCount++;
+            }
+        });
         
         if (outdentedCount > 0) {
             // NOTE: ProjectController handles recalc/save via Worker
@@ -630,10 +636,12 @@ export class TaskOperationsService {
         // UPDATE TOP-LEVEL TASKS (change parentId and sortKey)
         // =========================================================================
         
-        topLevelSelected.forEach((task, index) => {
-            projectController.updateTask(task.id, {
-                parentId: newParentId,
-                sortKey: sortKeys[index]
+        projectController.batch('Move Tasks', () => {
+            topLevelSelected.forEach((task, index) => {
+                projectController.updateTask(task.id, {
+                    parentId: newParentId,
+                    sortKey: sortKeys[index]
+                });
             });
         });
         
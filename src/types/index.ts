@@ -14,13 +14,15 @@ export type LinkType = 'FS' | 'SS' | 'FF' | 'SF';
 /**
  * Constraint types for task scheduling
  * - asap: As Soon As Possible (default)
+ * - alap: As Late As Possible
  * - snet: Start No Earlier Than
  * - snlt: Start No Later Than
  * - fnet: Finish No Earlier Than
  * - fnlt: Finish No Later Than
+ * - mso: Must Start On
  * - mfo: Must Finish On
  */
-export type ConstraintType = 'asap' | 'snet' | 'snlt' | 'fnet' | 'fnlt' | 'mfo';
+export type ConstraintType = 'asap' | 'alap' | 'snet' | 'snlt' | 'fnet' | 'fnlt' | 'mso' | 'mfo';
 
 /**
  * Task scheduling mode
@@ -48,6 +50,18 @@ export type RowType = 'task' | 'blank' | 'phantom';
  */
 export type HealthStatus = 'healthy' | 'at-risk' | 'critical' | 'blocked' | 'forced';
 
+/**
+ * Reason code behind a health status
+ */
+export type HealthReason =
+  | 'inCycle'
+  | 'missingPredecessor'
+  | 'negativeFloat'
+  | 'fnltSlipped'
+  | 'constraintViolated'
+  | 'lowFloat'
+  | 'manualMode';
+
 /**
  * Health indicator for a task
  * Provides at-a-glance status with progressive detail levels
@@ -55,6 +69,8 @@ export type HealthStatus = 'healthy' | 'at-risk' | 'critical' | 'blocked' | 'for
 export interface HealthIndicator {
   /** Overall health status */
   status: HealthStatus;
+  /** Everything found, most severe first (empty when healthy) */
+  reasons?: HealthReason[];
   /** Icon for display (emoji or can be mapped to SVG) */
   icon: string;
   /** One-line summary for tooltips */
@@ -95,11 +111,11 @@ export interface Task {
   wbs?: string;
   /** Hierarchy level (0 = root) */
   level: number;
-  /** Start date (ISO format: YYYY-MM-DD) */
+  /** Start date (ISO format: YYYY-MM-DD, or YYYY-MM-DDTHH:MM when scheduling in hours) */
   start: string;
-  /** End date (ISO format: YYYY-MM-DD) */
+  /** End date (ISO format: YYYY-MM-DD, or YYYY-MM-DDTHH:MM when scheduling in hours) */
   end: string;
-  /** Duration in working days */
+  /** Duration in working days (hours when scheduling in hours) */
   duration: number;
   /** Task dependencies */
   dependencies: Dependency[];
@@ -121,6 +137,9 @@ export interface Task {
    */
   schedulingMode?: SchedulingMode;
   
+  /** Named calendar the task works on (undefined = project calendar) */
+  calendarId?: string;
+  
   /** Notes/comments */
   notes: string;
   
@@ -236,10 +255,14 @@ export function isSchedulableTask(task: Task): boolean {
  * Calendar exception (non-working day or working exception)
  */
 export interface CalendarException {
-  /** Date (ISO format: YYYY-MM-DD) */
+  /** Date (ISO format: YYYY-MM-DD) - the first day of a range, same as its key */
   date: string;
+  /** Last day of a range (inclusive) - omit for a single day */
+  end?: string;
   /** True = working day, False = non-working day */
   working: boolean;
+  /** Working hours on these days when scheduling in hours (working exceptions only) */
+  hours?: WorkPeriod[];
   /** Optional description */
   description?: string;
 }
@@ -250,8 +273,50 @@ export interface CalendarException {
 export interface Calendar {
   /** Working days (0=Sunday, 1=Monday, ..., 6=Saturday) */
   workingDays: number[];
-  /** Date-specific exceptions - can be CalendarException object or string (legacy format) */
+  /**
+   * Date-specific exceptions keyed by date - can be CalendarException object or string (legacy format).
+   * Malformed entries are rejected with an error naming the key.
+   */
   exceptions: Record<string, CalendarException | string>;
+  /**
+   * Working hours by weekday (0=Sunday, ..., 6=Saturday), used when scheduling in hours.
+   * Working days without an entry work 08:00-12:00 and 13:00-17:00.
+   */
+  workHours?: Record<number, WorkPeriod[]>;
+  /** Recurring exception rules - date-specific `exceptions` take precedence */
+  rules?: CalendarRule[];
+}
+
+/**
+ * Recurring calendar exception (weekdays 0=Sunday..6=Saturday, months 1-12)
+ */
+export interface CalendarRule {
+  repeat:
+    | { type: 'annual'; month: number; day: number }
+    /** nth < 0 counts from the end of the month (-1 = last) */
+    | { type: 'nthWeekday'; month: number; weekday: number; nth: number }
+    | { type: 'range'; start: string; end: string }
+    | { type: 'weekly'; start: string; end: string; workingDays: number[] };
+  /** True = working on matching days; ignored by weekly overrides */
+  working?: boolean;
+  description?: string;
+}
+
+/**
+ * One concrete exception day (recurring rules expanded for display)
+ */
+export interface ExceptionDay {
+  date: string;
+  working: boolean;
+  description: string | null;
+}
+
+/**
+ * A stretch of working time within a day ("HH:MM"; end may be "24:00")
+ */
+export interface WorkPeriod {
+  start: string;
+  end: string;
 }
 
 // =============================================================================
@@ -291,8 +356,10 @@ export interface TaskTradePartnerAssignment {
  * CPM calculation result
  */
 export interface CPMResult {
-  /** Tasks with calculated dates and float */
+  /** Tasks with calculated dates and float - only the changed ones unless `full` */
   tasks: Task[];
+  /** True when every task was recalculated; otherwise merge `tasks` by ID */
+  full?: boolean;
   /** Calculation statistics */
   stats: {
     /** Calculation time in milliseconds */
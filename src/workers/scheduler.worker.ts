@@ -13,7 +13,7 @@
 
 // Import WASM module - wasm-pack generates these bindings
 import init, { SchedulerEngine } from '../../src-wasm/pkg/scheduler_wasm';
-import type { WorkerCommand, WorkerResponse } from './types';
+import type { WorkerCommand, WorkerResponse, SchedulerError } from './types';
 
 // The single source of truth - lives in WASM memory
 let engine: SchedulerEngine | null = null;
@@ -87,11 +87,11 @@ self.onmessage = async (e: MessageEvent<WorkerCommand>) => {
   try {
     switch (command.type) {
       case 'INITIALIZE': {
-        const { tasks, calendar } = command.payload;
+        const { tasks, calendar, dataDate } = command.payload;
         console.log(`[Worker] Initializing with ${tasks.length} tasks`);
         
         // Pass data to WASM engine
-        engine.initialize(tasks, calendar);
+        engine.initialize(tasks, calendar, dataDate);
         postResponse({ type: 'INITIALIZED', success: true });
         
         // Auto-calculate after initialization
@@ -122,6 +122,17 @@ self.onmessage = async (e: MessageEvent<WorkerCommand>) => {
         break;
       }
 
+      case 'APPLY_BATCH': {
+        // All operations and the recalculation in one engine call
+        const { operations, dataDate } = command.payload;
+        const batch = engine.apply_batch(operations, true, dataDate);
+        postResponse({ type: 'BATCH_APPLIED', payload: batch });
+        if (batch.result) {
+          postResponse({ type: 'CALCULATION_RESULT', payload: batch.result });
+        }
+        break;
+      }
+
       case 'SYNC_TASKS': {
         const { tasks } = command.payload;
         engine.sync_tasks(tasks);
@@ -141,11 +152,23 @@ self.onmessage = async (e: MessageEvent<WorkerCommand>) => {
       }
 
       case 'CALCULATE': {
-        const result = engine.calculate();
+        const result = engine.calculate(command.payload?.dataDate);
         postResponse({ type: 'CALCULATION_RESULT', payload: result });
         break;
       }
 
+      case 'VALIDATE_LOGIC': {
+        const issues = engine.validate_logic();
+        postResponse({ type: 'LOGIC_VALIDATED', payload: issues });
+        break;
+      }
+
+      case 'DCMA_CHECK': {
+        const checks = engine.dcma_check();
+        postResponse({ type: 'DCMA_CHECKED', payload: checks });
+        break;
+      }
+
       case 'DISPOSE': {
         if (engine) {
           engine.dispose();
@@ -165,12 +188,25 @@ self.onmessage = async (e: MessageEvent<WorkerCommand>) => {
     }
   } catch (err) {
     console.error('[Worker] Error processing command:', command.type, err);
+    // Engine failures are structured SchedulerError objects
+    const error = isSchedulerError(err) ? err : undefined;
+    const detail = error?.message ?? (err instanceof Error ? err.message : String(err));
     postResponse({ 
       type: 'ERROR', 
-      message: `Command ${command.type} failed: ${err instanceof Error ? err.message : String(err)}` 
+      message: `Command ${command.type} failed: ${detail}`,
+      error,
     });
   }
 };
 
+/**
+ * Check whether a thrown value is a SchedulerError from the engine
+ */
+function isSchedulerError(err: unknown): err is SchedulerError {
+  return typeof err === 'object' && err !== null
+    && typeof (err as SchedulerError).code === 'string'
+    && typeof (err as SchedulerError).message === 'string';
+}
+
 // Log worker startup
 console.log('[Worker] Scheduler worker spawned');
@@ -5,21 +5,137 @@
  * This ensures type safety across the postMessage boundary.
  */
 
-import type { Task, Calendar, CPMResult } from '../types';
+import type { Task, Calendar, CPMResult, LinkType } from '../types';
+
+/**
+ * One operation of an atomic batch (applied all together or not at all)
+ */
+export type BatchOperation =
+  | { op: 'create'; task: Task }
+  | { op: 'update'; id: string; updates: Partial<Task> }
+  | { op: 'delete'; id: string }
+  | { op: 'move'; id: string; parentId: string | null; sortKey?: string };
+
+/**
+ * Changes made by an applied batch
+ */
+export interface BatchResult {
+  created: string[];
+  updated: string[];
+  deleted: string[];
+  /** Present when the batch was recalculated */
+  result?: CPMResult;
+}
 
 /**
  * Commands sent TO the worker (Main → Worker)
  */
 export type WorkerCommand = 
-  | { type: 'INITIALIZE'; payload: { tasks: Task[]; calendar: Calendar } }
+  | { type: 'INITIALIZE'; payload: { tasks: Task[]; calendar: Calendar; dataDate?: string } }
   | { type: 'ADD_TASK'; payload: Task }
   | { type: 'UPDATE_TASK'; payload: { id: string; updates: Partial<Task> } }
   | { type: 'DELETE_TASK'; payload: { id: string } }
+  | { type: 'APPLY_BATCH'; payload: { operations: BatchOperation[]; dataDate?: string } }
   | { type: 'SYNC_TASKS'; payload: { tasks: Task[] } }
   | { type: 'UPDATE_CALENDAR'; payload: Calendar }
-  | { type: 'CALCULATE' }
+  | { type: 'CALCULATE'; payload?: { dataDate?: string } }
+  | { type: 'VALIDATE_LOGIC' }
+  | { type: 'DCMA_CHECK' }
   | { type: 'DISPOSE' };
 
+/**
+ * Machine-readable kind of engine failure
+ */
+export type SchedulerErrorCode =
+  | 'NotInitialized'
+  | 'TaskNotFound'
+  | 'CalendarNotFound'
+  | 'InvalidTask'
+  | 'InvalidField'
+  | 'InvalidOperation'
+  | 'InvalidCalendar'
+  | 'InvalidDate'
+  | 'InvalidOptions'
+  | 'CycleDetected'
+  | 'InvalidHierarchy'
+  | 'SerializationFailed';
+
+/**
+ * Structured error thrown by the WASM SchedulerEngine
+ */
+export interface SchedulerError {
+  code: SchedulerErrorCode;
+  message: string;
+  taskId?: string;
+  field?: string;
+  /** One entry per problem when several were found (e.g. per field) */
+  details?: SchedulerError[];
+}
+
+/**
+ * Problem with a task's parent found when tasks are loaded
+ */
+export interface HierarchyIssue {
+  kind: 'selfParent' | 'parentCycle' | 'danglingParent' | 'blankParent';
+  taskId: string;
+  parentId: string;
+  /** Task IDs around a parent cycle, each one's parent being the next */
+  cycle?: string[];
+  /** True when the task was re-attached (hierarchyPolicy "repair") */
+  repaired: boolean;
+  message: string;
+}
+
+/**
+ * Problem with a dependency link, found by `validate_logic`
+ */
+export interface LogicIssue {
+  kind:
+    | 'danglingPredecessor'
+    | 'selfLink'
+    | 'duplicateLink'
+    | 'ancestorLink'
+    | 'blankRowLink'
+    | 'lagExceedsDuration';
+  /** Task holding the link (the successor) */
+  taskId: string;
+  predecessorId: string;
+  linkType: LinkType;
+  lag: number;
+  message: string;
+}
+
+/**
+ * Result of one DCMA 14-point schedule check
+ */
+export interface DcmaCheck {
+  /** Check number (1-14) */
+  number: number;
+  metric:
+    | 'logic'
+    | 'leads'
+    | 'lags'
+    | 'relationshipTypes'
+    | 'hardConstraints'
+    | 'highFloat'
+    | 'negativeFloat'
+    | 'highDuration'
+    | 'invalidDates'
+    | 'resources'
+    | 'missedTasks'
+    | 'criticalPathTest'
+    | 'cpli'
+    | 'bei';
+  passed: boolean;
+  /** Measured value: a percentage, or an index for CPLI and BEI */
+  value: number;
+  /** Limit the value is held to */
+  threshold: number;
+  /** Tasks that count against the check */
+  taskIds: string[];
+  message: string;
+}
+
 /**
  * Responses sent FROM the worker (Worker → Main)
  */
@@ -27,8 +143,11 @@ export type WorkerResponse =
   | { type: 'READY' }
   | { type: 'INITIALIZED'; success: boolean }
   | { type: 'CALCULATION_RESULT'; payload: CPMResult }
+  | { type: 'BATCH_APPLIED'; payload: BatchResult }
   | { type: 'TASKS_SYNCED'; success: boolean }
-  | { type: 'ERROR'; message: string };
+  | { type: 'LOGIC_VALIDATED'; payload: LogicIssue[] }
+  | { type: 'DCMA_CHECKED'; payload: DcmaCheck[] }
+  | { type: 'ERROR'; message: string; error?: SchedulerError };
 
 /**
  * Worker state for tracking initialization
@@ -316,6 +316,78 @@ describe('Rollback Mechanism Integration (Phase 2)', () => {
     });
   });
 
+  describe('Batch rollback', () => {
+    it('should send one APPLY_BATCH and roll the whole batch back on error', () => {
+      const initialState = [...controller.tasks$.value];
+
+      controller.batch('Delete 2 Task(s)', () => {
+        controller.updateTask('task1', { name: 'Renamed' });
+        controller.deleteTask('task2');
+      });
+
+      // Both edits applied optimistically, sent as one command, one undo step
+      expect(controller.tasks$.value).toHaveLength(1);
+      expect(controller.tasks$.value[0].name).toBe('Renamed');
+      const commands = mockWorkerInstance!.postMessage.mock.calls.map(call => call[0]);
+      expect(commands.map(c => c.type)).toEqual(['APPLY_BATCH']);
+      expect(commands[0].payload.operations).toEqual([
+        { op: 'update', id: 'task1', updates: { name: 'Renamed' } },
+        { op: 'delete', id: 'task2' },
+      ]);
+      expect(historyManager.getUndoLabel()).toBe('Delete 2 Task(s)');
+
+      const errorResponse: WorkerResponse = {
+        type: 'ERROR',
+        message: 'Operation 1 (delete): Task not found: task2'
+      };
+      mockWorkerInstance!.onmessage!({ data: errorResponse } as MessageEvent);
+
+      // Nothing reached the engine, so the state and history go back as they were
+      expect(controller.tasks$.value).toEqual(initialState);
+      expect(historyManager.canUndo()).toBe(false);
+      expect(mockWorkerInstance!.postMessage).toHaveBeenCalledTimes(1);
+      expect(toastErrorSpy).toHaveBeenCalledWith(
+        'Applying changes failed: Operation 1 (delete): Task not found: task2'
+      );
+    });
+
+    it('should leave history to an outer composite when a batch inside it fails', () => {
+      historyManager.beginComposite('Outer');
+      controller.batch('Paste', () => {
+        controller.deleteTask('task2');
+      });
+
+      const errorResponse: WorkerResponse = { type: 'ERROR', message: 'Error' };
+      mockWorkerInstance!.onmessage!({ data: errorResponse } as MessageEvent);
+
+      // Still open for the caller that began it, and nothing undone
+      expect(historyManager.isInComposite()).toBe(true);
+      expect(controller.getTaskById('task2')).toBeDefined();
+      historyManager.endComposite();
+      expect(historyManager.getUndoLabel()).toBe('Outer');
+    });
+
+    it('should keep a batch once the worker has applied it', () => {
+      controller.batch('Move Tasks', () => {
+        controller.moveTask('task2', 'task1', 'a');
+      });
+
+      const applied: WorkerResponse = {
+        type: 'BATCH_APPLIED',
+        payload: { created: [], updated: ['task2'], deleted: [] }
+      };
+      mockWorkerInstance!.onmessage!({ data: applied } as MessageEvent);
+
+      // A later error has no pending batch to roll back
+      const errorResponse: WorkerResponse = { type: 'ERROR', message: 'Error' };
+      mockWorkerInstance!.onmessage!({ data: errorResponse } as MessageEvent);
+
+      expect(controller.getTaskById('task2')?.parentId).toBe('task1');
+      expect(controller.isCalculating$.value).toBe(false);
+      expect(historyManager.canUndo()).toBe(true);
+    });
+  });
+
   describe('Error notification', () => {
     it('should show error toast even if toastService not injected', () => {
       // Create controller without toast service